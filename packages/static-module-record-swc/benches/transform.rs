@@ -0,0 +1,108 @@
+//! `cargo bench --bench transform`
+//!
+//! Measures the transform time of large modules and prints the size of the compiled records.
+//! The synthetic modules mimic generated code: icon sets and locale tables with thousands of
+//! exports, and state modules that update their exported bindings everywhere.
+//! Set `BENCH_MODULES` to a directory to also benchmark every `.js` file inside it.
+
+use std::fmt::Write;
+use std::fs::{read_dir, read_to_string};
+use std::path::Path;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use swc_transformer_static_module_record::compile_module;
+
+/// `export const icon0 = ...` for every icon, like a generated icon set.
+fn many_exports(count: usize) -> String {
+    let mut code = String::from("import { createIcon } from './runtime.js'\n");
+    for i in 0..count {
+        writeln!(
+            code,
+            "export const icon{i} = createIcon('icon-{i}', 'M{i} 0h24v24H0z')"
+        )
+        .unwrap();
+    }
+    code
+}
+
+/// Bindings exported under several names and updated all over the module.
+fn many_updates(count: usize) -> String {
+    let mut code = String::new();
+    for i in 0..count {
+        writeln!(code, "let state{i} = 0").unwrap();
+        writeln!(code, "export {{ state{i}, state{i} as alias{i} }}").unwrap();
+    }
+    code.push_str("export function update() {\n");
+    for i in 0..count {
+        writeln!(
+            code,
+            "    state{i}++; state{i} += {i}; [state{i}] = [state{i} * 2]"
+        )
+        .unwrap();
+    }
+    code.push_str("}\n");
+    code
+}
+
+/// Destructured declarations, re-exports and imported bindings, like a module of a bundle.
+fn mixed(count: usize) -> String {
+    let mut code = String::from("import * as dep from './dep.js'\nexport * from './dep.js'\n");
+    for i in 0..count {
+        writeln!(code, "import {{ value{i} }} from './values.js'").unwrap();
+        writeln!(
+            code,
+            "export let {{ a{i}, b: [b{i}, ...c{i}] }} = dep.read(value{i})"
+        )
+        .unwrap();
+        writeln!(
+            code,
+            "export {{ value{i} as reexport{i} }} from './values.js'"
+        )
+        .unwrap();
+        writeln!(code, "for (a{i} of dep.list) b{i} = a{i} + value{i}").unwrap();
+    }
+    code
+}
+
+fn modules() -> Vec<(String, String)> {
+    let mut modules = vec![];
+    for count in [1_000, 5_000] {
+        modules.push((format!("many-exports-{count}"), many_exports(count)));
+        modules.push((format!("many-updates-{count}"), many_updates(count)));
+        modules.push((format!("mixed-{count}"), mixed(count)));
+    }
+    if let Ok(dir) = std::env::var("BENCH_MODULES") {
+        for entry in read_dir(Path::new(&dir)).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().is_some_and(|ext| ext == "js") {
+                let name = path.file_name().unwrap().to_string_lossy().to_string();
+                modules.push((name, read_to_string(&path).unwrap()));
+            }
+        }
+    }
+    modules
+}
+
+fn transform(c: &mut Criterion) {
+    let mut group = c.benchmark_group("transform");
+    group.sample_size(10);
+    for (name, source) in modules() {
+        let output = compile_module(&source, &name, Default::default())
+            .unwrap_or_else(|err| panic!("{}: {:#?}", name, err));
+        println!(
+            "{}: {} bytes -> {} bytes ({:.2}x)",
+            name,
+            source.len(),
+            output.len(),
+            output.len() as f64 / source.len() as f64
+        );
+        group.throughput(Throughput::Bytes(source.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(&name), &source, |b, source| {
+            b.iter(|| compile_module(source, &name, Default::default()).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, transform);
+criterion_main!(benches);
@@ -0,0 +1,32 @@
+//! `cargo fuzz run transform`
+//!
+//! Any input the parser accepts must compile without panicking into a module that parses again.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use swc_core::common::{sync::Lrc, FileName, SourceMap};
+use swc_core::ecma::ast::EsVersion;
+use swc_core::ecma::parser::{parse_file_as_module, Syntax};
+use swc_transformer_static_module_record::compile_module;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(source) = std::str::from_utf8(data) else {
+        return;
+    };
+    let Ok(output) = compile_module(source, "fuzz.js", Default::default()) else {
+        return;
+    };
+    let cm: Lrc<SourceMap> = Default::default();
+    let fm = cm.new_source_file(FileName::Anon, output.clone());
+    if parse_file_as_module(
+        &fm,
+        Syntax::default(),
+        EsVersion::latest(),
+        None,
+        &mut vec![],
+    )
+    .is_err()
+    {
+        panic!("output does not parse:\n{}", output);
+    }
+});
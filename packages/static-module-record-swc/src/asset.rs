@@ -0,0 +1,126 @@
+use std::fmt::Write;
+use std::path::Path;
+
+use crate::module::config::AssetKind;
+use crate::utils::{fnv1a, js_key, js_string};
+
+/// Generate an ES module whose default export is the asset `bytes` read from `file_name`.
+pub fn asset_to_module(kind: AssetKind, bytes: &[u8], file_name: &str) -> Result<String, String> {
+    let text =
+        || std::str::from_utf8(bytes).map_err(|_| format!("{:?} assets must be UTF-8", kind));
+    Ok(match kind {
+        AssetKind::Text => format!("export default {};\n", js_string(text()?)),
+        AssetKind::Bytes => format!(
+            "export default Uint8Array.from(atob(\"{}\"), (c) => c.charCodeAt(0));\n",
+            base64(bytes)
+        ),
+        AssetKind::CssModule => css_module(text()?, &format!("{:08x}", fnv1a(bytes))),
+        AssetKind::Url => {
+            let name = Path::new(file_name)
+                .file_name()
+                .map(|name| name.to_string_lossy())
+                .unwrap_or_default();
+            format!(
+                "export default new URL({}, import.meta.url).href;\n",
+                js_string(&format!("./{}", name))
+            )
+        }
+    })
+}
+
+/// ```js
+/// export default { "button": "button_hash" }
+/// export const stylesheet = ".button_hash { ... }"
+/// ```
+///
+/// Every class selector is suffixed with the hash of the stylesheet, so class names of different
+/// files never collide and the output does not depend on where the file is. The host inserts
+/// `stylesheet` into the document.
+fn css_module(css: &str, hash: &str) -> String {
+    let mut stylesheet = String::with_capacity(css.len());
+    let mut classes: Vec<&str> = vec![];
+    let mut rest = css;
+    while let Some(c) = rest.chars().next() {
+        let skipped = if rest.starts_with("/*") {
+            rest.find("*/").map_or(rest.len(), |end| end + 2)
+        } else if c == '"' || c == '\'' {
+            string_end(rest, c)
+        } else if rest.len() >= 4 && rest[..4].eq_ignore_ascii_case("url(") {
+            rest.find(')').map_or(rest.len(), |end| end + 1)
+        } else if c == '.' && rest[1..].starts_with(is_ident_start) {
+            let len = rest[1..]
+                .find(|c: char| !is_ident_char(c))
+                .unwrap_or(rest.len() - 1);
+            let class = &rest[1..1 + len];
+            if !classes.contains(&class) {
+                classes.push(class);
+            }
+            write!(stylesheet, ".{}_{}", class, hash).unwrap();
+            rest = &rest[1 + len..];
+            continue;
+        } else {
+            c.len_utf8()
+        };
+        stylesheet.push_str(&rest[..skipped]);
+        rest = &rest[skipped..];
+    }
+
+    let mut code = String::from("export default {");
+    for class in classes {
+        write!(
+            code,
+            " {}: {},",
+            js_key(class),
+            js_string(&format!("{}_{}", class, hash))
+        )
+        .unwrap();
+    }
+    writeln!(
+        code,
+        " }};\nexport const stylesheet = {};",
+        js_string(&stylesheet)
+    )
+    .unwrap();
+    code
+}
+
+/// The length of the string literal at the start of `css`, including the quotes.
+fn string_end(css: &str, quote: char) -> usize {
+    let mut escaped = false;
+    for (index, c) in css.char_indices().skip(1) {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            _ if c == quote => return index + 1,
+            _ => {}
+        }
+    }
+    css.len()
+}
+
+fn is_ident_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_' || c == '-' || !c.is_ascii()
+}
+
+fn is_ident_char(c: char) -> bool {
+    is_ident_start(c) || c.is_ascii_digit()
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut result = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, byte)| n | u32::from(*byte) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                result.push(char::from(ALPHABET[(n >> (18 - 6 * i) & 63) as usize]));
+            } else {
+                result.push('=');
+            }
+        }
+    }
+    result
+}
@@ -0,0 +1,197 @@
+//! `static-module-record [--config <json>] [--script] [-o <output>] [--manifest <file>] <input>`
+//! `static-module-record [--config <json>] --bundle <callback> [--source-map <file>] [-o <output>] <entry>`
+//! `static-module-record [--config <json>] --check <entry>`
+//! `static-module-record [--config <json>] --link [-o <output>] <entry>`
+//!
+//! Compile an ES module, a JSON module (`.json`), a WebAssembly module (`.wasm`) or an asset of
+//! `config.assets` into a VirtualModuleRecord. With `--script`, the input is a classic script.
+//! The record is written to stdout unless `-o` is given. `--manifest` writes the manifest of the
+//! record as JSON.
+//!
+//! With `--bundle`, the entry and the modules it imports are registered by the callback in one
+//! script. `--source-map` writes the source map of the script.
+//!
+//! With `--check`, the link errors of the entry and the modules it imports are printed. With
+//! `--link`, the link table of the graph is written as JSON.
+
+use std::fs::{read, write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use swc_transformer_static_module_record::{
+    bundle, check_graph, compile_file_with_manifest, compile_script_with_manifest, config::Config,
+    link_graph,
+};
+
+const USAGE: &str = "usage: static-module-record [--config <json>] [--script] [-o <output>] \
+                     [--manifest <file>] <input>
+       static-module-record [--config <json>] --bundle <callback> [--source-map <file>] \
+                     [-o <output>] <entry>
+       static-module-record [--config <json>] --check <entry>
+       static-module-record [--config <json>] --link [-o <output>] <entry>";
+
+struct Args {
+    config: Config,
+    input: PathBuf,
+    output: Option<PathBuf>,
+    manifest: Option<PathBuf>,
+    script: bool,
+    bundle: Option<String>,
+    source_map: Option<PathBuf>,
+    check: bool,
+    link: bool,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut config = None;
+    let mut input = None;
+    let mut output = None;
+    let mut manifest = None;
+    let mut script = false;
+    let mut bundle = None;
+    let mut source_map = None;
+    let mut check = false;
+    let mut link = false;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--config" => {
+                let json = args.next().ok_or("--config requires a value")?;
+                config = Some(serde_json::from_str(&json).map_err(|err| err.to_string())?);
+            }
+            "--script" => script = true,
+            "-o" | "--output" => output = Some(args.next().ok_or("-o requires a value")?.into()),
+            "--manifest" => {
+                manifest = Some(args.next().ok_or("--manifest requires a value")?.into())
+            }
+            "--bundle" => bundle = Some(args.next().ok_or("--bundle requires a value")?),
+            "--check" => check = true,
+            "--link" => link = true,
+            "--source-map" => {
+                source_map = Some(args.next().ok_or("--source-map requires a value")?.into())
+            }
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            _ if input.is_none() && !arg.starts_with('-') => input = Some(arg.into()),
+            _ => return Err(format!("unexpected argument {}\n{}", arg, USAGE)),
+        }
+    }
+    if bundle.is_some() && (script || manifest.is_some()) {
+        return Err("--bundle can not be used with --script or --manifest".to_string());
+    }
+    if check && (bundle.is_some() || script || manifest.is_some() || output.is_some()) {
+        return Err("--check can not be used with other options than --config".to_string());
+    }
+    if link && (check || bundle.is_some() || script || manifest.is_some()) {
+        return Err("--link can not be used with other options than --config and -o".to_string());
+    }
+    if bundle.is_none() && source_map.is_some() {
+        return Err("--source-map requires --bundle".to_string());
+    }
+    Ok(Args {
+        config: config.unwrap_or_default(),
+        input: input.ok_or(USAGE)?,
+        output,
+        manifest,
+        script,
+        bundle,
+        source_map,
+        check,
+        link,
+    })
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("{}", err);
+            return ExitCode::FAILURE;
+        }
+    };
+    if args.check {
+        return match check_graph(&args.input.to_string_lossy(), args.config) {
+            Ok(warnings) => {
+                warnings.iter().for_each(|message| eprintln!("{}", message));
+                ExitCode::SUCCESS
+            }
+            Err(diagnostics) => report(&diagnostics),
+        };
+    }
+    if args.link {
+        return match link_graph(&args.input.to_string_lossy(), args.config) {
+            Ok(table) => {
+                let json = serde_json::to_string_pretty(&table).unwrap();
+                write_output(args.output.as_deref(), json + "\n")
+            }
+            Err(diagnostics) => report(&diagnostics),
+        };
+    }
+    if let Some(callback_name) = &args.bundle {
+        return match bundle(&args.input.to_string_lossy(), callback_name, args.config) {
+            Ok(bundle) => {
+                let mut code = bundle.code;
+                if let Some(path) = &args.source_map {
+                    if let Err(err) = write(path, bundle.source_map) {
+                        eprintln!("{}: {}", path.display(), err);
+                        return ExitCode::FAILURE;
+                    }
+                    let url = path.file_name().unwrap_or(path.as_os_str());
+                    code += &format!("//# sourceMappingURL={}\n", url.to_string_lossy());
+                }
+                write_output(args.output.as_deref(), code)
+            }
+            Err(diagnostics) => report(&diagnostics),
+        };
+    }
+    let source = match read(&args.input) {
+        Ok(source) => source,
+        Err(err) => {
+            eprintln!("{}: {}", args.input.display(), err);
+            return ExitCode::FAILURE;
+        }
+    };
+    let file_name = args.input.to_string_lossy();
+    let result = if args.script {
+        String::from_utf8(source)
+            .map_err(|err| vec![format!("{}: {}", file_name, err)])
+            .and_then(|source| compile_script_with_manifest(&source, &file_name, args.config))
+    } else {
+        compile_file_with_manifest(&source, &file_name, args.config)
+    };
+    match result {
+        Ok((code, manifest)) => {
+            if let Some(path) = args.manifest {
+                let json = serde_json::to_string_pretty(&manifest).unwrap();
+                if let Err(err) = write(&path, json + "\n") {
+                    eprintln!("{}: {}", path.display(), err);
+                    return ExitCode::FAILURE;
+                }
+            }
+            write_output(args.output.as_deref(), code)
+        }
+        Err(diagnostics) => report(&diagnostics),
+    }
+}
+
+fn write_output(output: Option<&Path>, code: String) -> ExitCode {
+    match output {
+        Some(output) => {
+            if let Err(err) = write(output, code) {
+                eprintln!("{}: {}", output.display(), err);
+                return ExitCode::FAILURE;
+            }
+            ExitCode::SUCCESS
+        }
+        None => {
+            print!("{}", code);
+            ExitCode::SUCCESS
+        }
+    }
+}
+
+fn report(diagnostics: &[String]) -> ExitCode {
+    diagnostics
+        .iter()
+        .for_each(|message| eprintln!("{}", message));
+    ExitCode::FAILURE
+}
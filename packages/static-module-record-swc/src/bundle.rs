@@ -0,0 +1,82 @@
+use std::path::Path;
+
+use swc_core::common::comments::SingleThreadedComments;
+use swc_core::common::{sync::Lrc, SourceMap, DUMMY_SP};
+use swc_core::ecma::ast::*;
+
+use crate::compiler::{collect_diagnostics, print_with_source_map};
+use crate::graph::Graph;
+use crate::module::config::{Config, Template};
+
+/// A module graph compiled into one script.
+#[derive(Debug, Clone)]
+pub struct Bundle {
+    /// `"use strict"; callback_name("/key.js", { ... }); ...` for every module of the graph.
+    pub code: String,
+    /// The source map of `code`, its sources are the files of the graph.
+    pub source_map: String,
+}
+
+/// Compile `entry` and the modules it imports into one script.
+///
+/// The graph follows the `from` of the bindings and the string literals passed to `import()`.
+/// Each module is registered by `callback_name` under its specifier resolved against the
+/// directory of `entry`, like the URL path `/dir/file.js`, so the host can resolve the
+/// specifiers of a record against its key. Bare specifiers are left to the host.
+pub fn bundle(entry: &str, callback_name: &str, config: Config) -> Result<Bundle, Vec<String>> {
+    let cm: Lrc<SourceMap> = Default::default();
+    let comments = SingleThreadedComments::default();
+    let mut errors = vec![];
+    let result = collect_diagnostics(&cm, |handler| {
+        let graph = Graph::load(
+            &cm,
+            &comments,
+            handler,
+            Path::new(entry),
+            |key| Config {
+                template: Template::Callback {
+                    callback_name: callback_name.to_string(),
+                    first_arg: key.to_string(),
+                },
+                ..config.clone()
+            },
+            &mut errors,
+        );
+        let mut body: Vec<ModuleItem> = vec![Stmt::Expr(ExprStmt {
+            span: DUMMY_SP,
+            expr: Box::new("use strict".into()),
+        })
+        .into()];
+        for module in graph.modules {
+            // The "use strict" directive of the template is the first statement of the bundle.
+            body.extend(
+                module
+                    .module
+                    .body
+                    .into_iter()
+                    .filter(|item| !is_use_strict(item)),
+            );
+        }
+        Some(print_with_source_map(
+            &cm,
+            &Module {
+                span: DUMMY_SP,
+                body,
+                shebang: None,
+            },
+        ))
+    });
+    match result {
+        Ok((code, source_map)) if errors.is_empty() => Ok(Bundle { code, source_map }),
+        Ok(_) => Err(errors),
+        Err(diagnostics) => Err(errors.into_iter().chain(diagnostics).collect()),
+    }
+}
+
+fn is_use_strict(item: &ModuleItem) -> bool {
+    matches!(
+        item,
+        ModuleItem::Stmt(Stmt::Expr(ExprStmt { expr, .. }))
+            if matches!(&**expr, Expr::Lit(Lit::Str(str)) if &*str.value == "use strict")
+    )
+}
@@ -0,0 +1,403 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use swc_core::common::comments::SingleThreadedComments;
+use swc_core::common::errors::{DiagnosticBuilder, Emitter, Handler, Level, HANDLER};
+use swc_core::common::source_map::LineCol;
+use swc_core::common::{
+    sync::Lrc, BytePos, FileName, Globals, Mark, SourceFile, SourceMap, Span, GLOBALS,
+};
+use swc_core::ecma::ast::{EsVersion, Module, Program};
+use swc_core::ecma::codegen::{text_writer::JsWriter, Emitter as CodeEmitter};
+use swc_core::ecma::parser::{
+    error::Error, parse_file_as_module, parse_file_as_script, EsConfig, PResult, Syntax,
+};
+use swc_core::ecma::transforms::base::{fixer::fixer, hygiene::hygiene, resolver};
+use swc_core::ecma::visit::FoldWith;
+
+use crate::asset::asset_to_module;
+use crate::json::{parse_json_module, NOT_A_JSON_VALUE};
+use crate::manifest::Manifest;
+use crate::module::{
+    config::{AssetKind, Config},
+    VirtualModuleRecordTransformer,
+};
+use crate::script::script_to_module;
+use crate::utils::emit_error;
+use crate::wasm::{parse_wasm, wasm_to_module};
+
+/// Compile an ES module into a VirtualModuleRecord without @swc/core.
+///
+/// This runs the same pipeline as the plugin (resolver, transformer, hygiene and fixer). All
+/// diagnostics are returned as the error if any of them is an error.
+pub fn compile_module(
+    source: &str,
+    file_name: &str,
+    config: Config,
+) -> Result<String, Vec<String>> {
+    module(source, file_name, config).map(|(code, _)| code)
+}
+
+/// Compile a classic script into a VirtualModuleRecord that runs in the global scope.
+///
+/// See [`script_to_module`] for how the global declarations of the script are kept.
+pub fn compile_script(
+    source: &str,
+    file_name: &str,
+    config: Config,
+) -> Result<String, Vec<String>> {
+    compile_script_with_manifest(source, file_name, config).map(|(code, _)| code)
+}
+
+/// Same as [`compile_script`], and also returns the [`Manifest`] of the record.
+pub fn compile_script_with_manifest(
+    source: &str,
+    file_name: &str,
+    config: Config,
+) -> Result<(String, Manifest), Vec<String>> {
+    compile(
+        source,
+        source.as_bytes(),
+        file_name,
+        config,
+        |fm, comments, handler| {
+            let mut recovered = vec![];
+            let script = parse_file_as_script(
+                fm,
+                Syntax::Es(Default::default()),
+                EsVersion::latest(),
+                Some(comments),
+                &mut recovered,
+            );
+            report_parse_errors(script, recovered, handler).map(Program::Script)
+        },
+    )
+}
+
+/// Compile a JSON module into a VirtualModuleRecord with a single `default` export.
+///
+/// The record is emitted with the same template as JavaScript modules.
+pub fn compile_json(source: &str, file_name: &str, config: Config) -> Result<String, Vec<String>> {
+    json(source, file_name, config).map(|(code, _)| code)
+}
+
+/// Compile a WebAssembly binary into a VirtualModuleRecord.
+///
+/// Each import of the binary becomes an import binding, each export an export binding, and
+/// `execute` instantiates the inlined binary with the imported bindings.
+pub fn compile_wasm(bytes: &[u8], file_name: &str, config: Config) -> Result<String, Vec<String>> {
+    wasm(bytes, file_name, config).map(|(code, _)| code)
+}
+
+/// Compile an asset into a VirtualModuleRecord whose default export is the content of the asset.
+pub fn compile_asset(
+    bytes: &[u8],
+    file_name: &str,
+    kind: AssetKind,
+    config: Config,
+) -> Result<String, Vec<String>> {
+    asset(bytes, file_name, kind, config).map(|(code, _)| code)
+}
+
+/// Compile a file by its name: assets of `config.assets`, then `.wasm`, `.json` and ES modules.
+pub fn compile_file(bytes: &[u8], file_name: &str, config: Config) -> Result<String, Vec<String>> {
+    compile_file_with_manifest(bytes, file_name, config).map(|(code, _)| code)
+}
+
+/// Same as [`compile_file`], and also returns the [`Manifest`] of the record.
+pub fn compile_file_with_manifest(
+    bytes: &[u8],
+    file_name: &str,
+    config: Config,
+) -> Result<(String, Manifest), Vec<String>> {
+    let source = Source::read(bytes, file_name, &config)?;
+    let (source, parse) = source.parser();
+    compile(source, bytes, file_name, config, parse)
+}
+
+type Compiled = Result<(String, Manifest), Vec<String>>;
+type Parse = fn(&SourceFile, &SingleThreadedComments, &Handler) -> Option<Program>;
+
+/// A file of any kind, as the source text of an ES module or a JSON module.
+pub(crate) enum Source<'a> {
+    Module(Cow<'a, str>),
+    Json(&'a str),
+}
+
+impl<'a> Source<'a> {
+    /// Assets of `config.assets` and WebAssembly binaries are converted into ES modules.
+    pub(crate) fn read(
+        bytes: &'a [u8],
+        file_name: &str,
+        config: &Config,
+    ) -> Result<Self, Vec<String>> {
+        if let Some(kind) = config.asset_kind(file_name) {
+            return asset_source(bytes, file_name, kind)
+                .map(|source| Source::Module(source.into()));
+        }
+        if file_name.ends_with(".wasm") {
+            return wasm_source(bytes, file_name).map(|source| Source::Module(source.into()));
+        }
+        let source = std::str::from_utf8(bytes)
+            .map_err(|err| vec![format!("{}: error: {}", file_name, err)])?;
+        if file_name.ends_with(".json") {
+            Ok(Source::Json(source))
+        } else {
+            Ok(Source::Module(source.into()))
+        }
+    }
+    pub(crate) fn parser(&self) -> (&str, Parse) {
+        match self {
+            Source::Module(source) => (source, parse_module),
+            Source::Json(source) => (source, parse_json),
+        }
+    }
+}
+
+fn module(source: &str, file_name: &str, config: Config) -> Compiled {
+    compile(source, source.as_bytes(), file_name, config, parse_module)
+}
+
+fn json(source: &str, file_name: &str, config: Config) -> Compiled {
+    compile(source, source.as_bytes(), file_name, config, parse_json)
+}
+
+fn wasm(bytes: &[u8], file_name: &str, config: Config) -> Compiled {
+    let source = wasm_source(bytes, file_name)?;
+    compile(&source, bytes, file_name, config, parse_module)
+}
+
+fn asset(bytes: &[u8], file_name: &str, kind: AssetKind, config: Config) -> Compiled {
+    let source = asset_source(bytes, file_name, kind)?;
+    compile(&source, bytes, file_name, config, parse_module)
+}
+
+fn wasm_source(bytes: &[u8], file_name: &str) -> Result<String, Vec<String>> {
+    let interface =
+        parse_wasm(bytes).map_err(|err| vec![format!("{}: error: {}", file_name, err)])?;
+    Ok(wasm_to_module(bytes, &interface))
+}
+
+fn asset_source(bytes: &[u8], file_name: &str, kind: AssetKind) -> Result<String, Vec<String>> {
+    asset_to_module(kind, bytes, file_name)
+        .map_err(|err| vec![format!("{}: error: {}", file_name, err)])
+}
+
+fn parse_module(
+    fm: &SourceFile,
+    comments: &SingleThreadedComments,
+    handler: &Handler,
+) -> Option<Program> {
+    let mut recovered = vec![];
+    let module = parse_file_as_module(
+        fm,
+        Syntax::Es(EsConfig {
+            explicit_resource_management: true,
+            ..Default::default()
+        }),
+        EsVersion::latest(),
+        Some(comments),
+        &mut recovered,
+    );
+    report_parse_errors(module, recovered, handler).map(Program::Module)
+}
+
+fn parse_json(
+    fm: &SourceFile,
+    comments: &SingleThreadedComments,
+    _handler: &Handler,
+) -> Option<Program> {
+    parse_json_module(fm, Some(comments))
+        .map(Program::Module)
+        .map_err(|span| emit_error(span, NOT_A_JSON_VALUE))
+        .ok()
+}
+
+/// `input` is the file that `source` was read from, it is hashed for `config.hash`.
+fn compile(
+    source: &str,
+    input: &[u8],
+    file_name: &str,
+    config: Config,
+    parse: impl FnOnce(&SourceFile, &SingleThreadedComments, &Handler) -> Option<Program>,
+) -> Compiled {
+    let cm: Lrc<SourceMap> = Default::default();
+    let comments = SingleThreadedComments::default();
+    collect_diagnostics(&cm, |handler| {
+        let fm = cm.new_source_file(FileName::Real(PathBuf::from(file_name)), source.into());
+        let (module, transformer) = transform(
+            &fm,
+            input,
+            &comments,
+            config,
+            HashMap::new(),
+            handler,
+            parse,
+        )?;
+        Some((print(&cm, &module), transformer.manifest()))
+    })
+}
+
+/// Run `f` with a diagnostics handler, all diagnostics are returned if any of them is an error.
+pub(crate) fn collect_diagnostics<T>(
+    cm: &Lrc<SourceMap>,
+    f: impl FnOnce(&Handler) -> Option<T>,
+) -> Result<T, Vec<String>> {
+    collect_all_diagnostics(cm, f).map(|(output, _)| output)
+}
+
+/// Same as [`collect_diagnostics`], and also returns the warnings when there is no error.
+pub(crate) fn collect_all_diagnostics<T>(
+    cm: &Lrc<SourceMap>,
+    f: impl FnOnce(&Handler) -> Option<T>,
+) -> Result<(T, Vec<String>), Vec<String>> {
+    let diagnostics = Arc::new(Mutex::new(vec![]));
+    let handler = Handler::with_emitter(
+        true,
+        false,
+        Box::new(CollectDiagnostics {
+            cm: cm.clone(),
+            diagnostics: diagnostics.clone(),
+        }),
+    );
+    let output = GLOBALS.set(&Globals::new(), || HANDLER.set(&handler, || f(&handler)));
+    let diagnostics = diagnostics.lock().unwrap().drain(..).collect();
+    if handler.has_errors() {
+        return Err(diagnostics);
+    }
+    Ok((
+        output.expect("the parser should report an error"),
+        diagnostics,
+    ))
+}
+
+/// Parse `fm` and run the same pipeline as the plugin (resolver, transformer, hygiene and fixer).
+///
+/// `input` is the file `fm` was read from, the binary of WebAssembly and assets.
+///
+/// `star_exports` are the names of `export * from` by the specifier, see
+/// [`Config::expand_star_exports`].
+pub(crate) fn transform(
+    fm: &SourceFile,
+    input: &[u8],
+    comments: &SingleThreadedComments,
+    config: Config,
+    star_exports: HashMap<String, Vec<String>>,
+    handler: &Handler,
+    parse: impl FnOnce(&SourceFile, &SingleThreadedComments, &Handler) -> Option<Program>,
+) -> Option<(Module, VirtualModuleRecordTransformer)> {
+    let program = parse(fm, comments, handler)?;
+
+    let unresolved_mark = Mark::new();
+    let top_level_mark = Mark::new();
+    let program = match program.fold_with(&mut resolver(unresolved_mark, top_level_mark, false)) {
+        Program::Script(script) => script_to_module(script, unresolved_mark),
+        Program::Module(module) => module,
+    };
+    let mut transformer =
+        VirtualModuleRecordTransformer::new(config, Some(fm.name.to_string()), unresolved_mark)
+            .with_comments(comments.clone())
+            .with_source(input)
+            .with_star_exports(star_exports);
+    let program = Program::Module(program)
+        .fold_with(&mut transformer)
+        .fold_with(&mut hygiene())
+        .fold_with(&mut fixer(Some(comments)));
+    Some((program.expect_module(), transformer))
+}
+
+fn report_parse_errors<T>(
+    result: PResult<T>,
+    recovered: Vec<Error>,
+    handler: &Handler,
+) -> Option<T> {
+    match result {
+        Ok(node) if recovered.is_empty() => Some(node),
+        Ok(_) => {
+            recovered
+                .into_iter()
+                .for_each(|err| err.into_diagnostic(handler).emit());
+            None
+        }
+        Err(err) => {
+            err.into_diagnostic(handler).emit();
+            None
+        }
+    }
+}
+
+pub(crate) fn print(cm: &Lrc<SourceMap>, module: &Module) -> String {
+    print_with_mappings(cm, module, None)
+}
+
+/// Print `module` and its source map, the sources are the files of `cm`.
+pub(crate) fn print_with_source_map(cm: &Lrc<SourceMap>, module: &Module) -> (String, String) {
+    let mut mappings = vec![];
+    let code = print_with_mappings(cm, module, Some(&mut mappings));
+    let mut source_map = vec![];
+    cm.build_source_map(&mappings)
+        .to_writer(&mut source_map)
+        .unwrap();
+    (code, String::from_utf8(source_map).unwrap())
+}
+
+fn print_with_mappings(
+    cm: &Lrc<SourceMap>,
+    module: &Module,
+    mappings: Option<&mut Vec<(BytePos, LineCol)>>,
+) -> String {
+    let mut buf = vec![];
+    {
+        let mut emitter = CodeEmitter {
+            cfg: Default::default(),
+            cm: cm.clone(),
+            comments: None,
+            wr: JsWriter::new(cm.clone(), "\n", &mut buf, mappings),
+        };
+        emitter.emit_module(module).unwrap();
+    }
+    String::from_utf8(buf).unwrap()
+}
+
+/// Collect the diagnostics as `file:line:column: message`.
+struct CollectDiagnostics {
+    cm: Lrc<SourceMap>,
+    diagnostics: Arc<Mutex<Vec<String>>>,
+}
+
+impl Emitter for CollectDiagnostics {
+    fn emit(&mut self, db: &DiagnosticBuilder<'_>) {
+        let level = match db.level {
+            Level::Warning => "warning",
+            Level::Note | Level::Help => "note",
+            _ => "error",
+        };
+        let mut message = match db.span.primary_span() {
+            Some(span) if !span.is_dummy() => {
+                format!("{}: {}: {}", self.location(span), level, db.message())
+            }
+            _ => format!("{}: {}", level, db.message()),
+        };
+        // Labels of other spans, like the export of another module, are notes on their own line.
+        let labels = match db.span.primary_span() {
+            Some(_) => db.span.span_labels(),
+            None => vec![],
+        };
+        for label in labels {
+            if let (false, Some(note)) = (label.is_primary || label.span.is_dummy(), label.label) {
+                message += &format!("\n{}: note: {}", self.location(label.span), note);
+            }
+        }
+        self.diagnostics.lock().unwrap().push(message);
+    }
+}
+
+impl CollectDiagnostics {
+    /// `file:line:column` of the start of `span`.
+    fn location(&self, span: Span) -> String {
+        let loc = self.cm.lookup_char_pos(span.lo);
+        format!("{}:{}:{}", loc.file.name, loc.line, loc.col_display + 1)
+    }
+}
@@ -0,0 +1,222 @@
+use std::collections::{HashSet, VecDeque};
+use std::path::Path;
+
+use swc_core::common::comments::SingleThreadedComments;
+use swc_core::common::errors::Level;
+use swc_core::common::{sync::Lrc, SourceMap, Span};
+
+use super::resolution::{Export, Resolution};
+use super::Graph;
+use crate::compiler::collect_all_diagnostics;
+use crate::module::binding_descriptor::{module_export_name_to_str, Binding, ModuleBinding};
+use crate::module::config::Config;
+use crate::utils::emit_with_labels;
+
+/// Compile `entry` and the modules it imports, and report what the link phase of a compartment
+/// would fail on or silently drop:
+///
+/// - (error) an import or re-export of a name the module does not export,
+/// - (error) an import or re-export of a name provided by more than one `export *`,
+/// - (warning) a name provided by more than one `export *`, which is not exported,
+/// - (warning) import cycles.
+///
+/// Modules outside of the graph (bare specifiers) are not checked. The warnings are returned if
+/// there is no error, each diagnostic has notes at the related code in the other files.
+pub fn check_graph(entry: &str, config: Config) -> Result<Vec<String>, Vec<String>> {
+    let cm: Lrc<SourceMap> = Default::default();
+    let comments = SingleThreadedComments::default();
+    let mut errors = vec![];
+    let result = collect_all_diagnostics(&cm, |handler| {
+        let graph = Graph::load(
+            &cm,
+            &comments,
+            handler,
+            Path::new(entry),
+            |_| config.clone(),
+            &mut errors,
+        );
+        check_imports(&graph);
+        check_star_exports(&graph);
+        check_cycles(&graph);
+        Some(())
+    });
+    match result {
+        Ok(((), warnings)) if errors.is_empty() => Ok(warnings),
+        Ok((_, warnings)) => Err(errors.into_iter().chain(warnings).collect()),
+        Err(diagnostics) => Err(errors.into_iter().chain(diagnostics).collect()),
+    }
+}
+
+impl Graph {
+    /// `name of /mod.js`, for notes.
+    fn describe(&self, export: &Export) -> String {
+        let key = &self.modules[export.module].key;
+        match &*export.local {
+            "*" => format!("the namespace of `{}`", key),
+            local => format!("`{}` of `{}`", local, key),
+        }
+    }
+}
+
+/// Imports and re-exports of names that can not be resolved.
+pub(super) fn check_imports(graph: &Graph) {
+    for (module, graph_module) in graph.modules.iter().enumerate() {
+        for binding in &graph_module.bindings {
+            let (name, from, span) = match binding {
+                Binding::Import(import) => match &import.import {
+                    ModuleBinding::ModuleExportName(name) => {
+                        (name, &import.from, graph.span_of(module, name))
+                    }
+                    _ => continue,
+                },
+                Binding::Export(export) => match (&export.export, &export.from) {
+                    (ModuleBinding::ModuleExportName(name), Some(from)) => {
+                        (name, from, graph.span_of(module, name))
+                    }
+                    _ => continue,
+                },
+            };
+            // `import x from` has no span for `default`.
+            let span = if span == graph_module.span {
+                from.span
+            } else {
+                span
+            };
+            let Some(target) = graph.resolve(module, &from.value) else {
+                continue;
+            };
+            let name = module_export_name_to_str(name);
+            match graph.resolve_export(target, &name, &mut HashSet::new()) {
+                Resolution::NotFound => emit_with_labels(
+                    Level::Error,
+                    span,
+                    &format!("`{}` does not export `{}`.", from.value, name),
+                    &[(
+                        graph.modules[target].span,
+                        format!("`{}` is imported from here", graph.modules[target].key),
+                    )],
+                ),
+                Resolution::Ambiguous(a, b) => emit_with_labels(
+                    Level::Error,
+                    span,
+                    &format!(
+                        "`{}` of `{}` is ambiguous, more than one `export *` provides it.",
+                        name, from.value
+                    ),
+                    &[
+                        (a.span, format!("{} is exported here", graph.describe(&a))),
+                        (b.span, format!("{} is exported here", graph.describe(&b))),
+                    ],
+                ),
+                Resolution::Found(_) | Resolution::Unknown => {}
+            }
+        }
+    }
+}
+
+/// Names that more than one `export *` provides are silently excluded from the exports.
+fn check_star_exports(graph: &Graph) {
+    for (module, graph_module) in graph.modules.iter().enumerate() {
+        let Some(first_star) = graph.star_exports(module).next() else {
+            continue;
+        };
+        let explicit = graph.explicit_exports(module);
+        for name in graph.exported_names(module, &mut HashSet::new()) {
+            if explicit.contains(&name) {
+                continue;
+            }
+            if let Resolution::Ambiguous(a, b) =
+                graph.resolve_export(module, &name, &mut HashSet::new())
+            {
+                emit_with_labels(
+                    Level::Warning,
+                    first_star.span,
+                    &format!(
+                        "`{}` is provided by more than one `export *`, `{}` does not export it.",
+                        name, graph_module.key
+                    ),
+                    &[
+                        (a.span, format!("{} is exported here", graph.describe(&a))),
+                        (b.span, format!("{} is exported here", graph.describe(&b))),
+                    ],
+                );
+            }
+        }
+    }
+}
+
+/// Each module is reported in one cycle at most.
+fn check_cycles(graph: &Graph) {
+    // The static imports of every module and the span of their first `from`.
+    let edges: Vec<Vec<(usize, Span)>> = (0..graph.modules.len())
+        .map(|module| {
+            let mut edges: Vec<(usize, Span)> = vec![];
+            for binding in &graph.modules[module].bindings {
+                let from = match binding {
+                    Binding::Import(import) => &import.from,
+                    Binding::Export(export) => match &export.from {
+                        Some(from) => from,
+                        None => continue,
+                    },
+                };
+                if let Some(target) = graph.resolve(module, &from.value) {
+                    if edges.iter().all(|(edge, _)| *edge != target) {
+                        edges.push((target, from.span));
+                    }
+                }
+            }
+            edges
+        })
+        .collect();
+    let mut reported = HashSet::new();
+    for start in 0..graph.modules.len() {
+        if reported.contains(&start) {
+            continue;
+        }
+        // The shortest path from `start` back to `start`, `previous` is the edge into a module.
+        let mut previous: Vec<Option<(usize, Span)>> = vec![None; graph.modules.len()];
+        let mut queue = VecDeque::from([start]);
+        while let Some(module) = queue.pop_front() {
+            for &(target, span) in &edges[module] {
+                if previous[target].is_none() && !reported.contains(&target) {
+                    previous[target] = Some((module, span));
+                    queue.push_back(target);
+                }
+            }
+            if previous[start].is_some() {
+                break;
+            }
+        }
+        if previous[start].is_none() {
+            continue;
+        }
+        let mut cycle = vec![];
+        let mut module = start;
+        loop {
+            let (importer, span) = previous[module].unwrap();
+            cycle.push((importer, module, span));
+            module = importer;
+            if module == start {
+                break;
+            }
+        }
+        cycle.reverse();
+        reported.extend(cycle.iter().map(|(importer, _, _)| *importer));
+        let path = cycle
+            .iter()
+            .map(|(importer, _, _)| graph.modules[*importer].key.as_str())
+            .chain([graph.modules[start].key.as_str()])
+            .collect::<Vec<_>>()
+            .join(" -> ");
+        let labels: Vec<(Span, String)> = cycle[1..]
+            .iter()
+            .map(|(_, target, span)| (*span, format!("imports `{}`", graph.modules[*target].key)))
+            .collect();
+        emit_with_labels(
+            Level::Warning,
+            cycle[0].2,
+            &format!("Import cycle: {}.", path),
+            &labels,
+        );
+    }
+}
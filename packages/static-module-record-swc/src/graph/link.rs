@@ -0,0 +1,120 @@
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+
+use serde::Serialize;
+use swc_core::common::comments::SingleThreadedComments;
+use swc_core::common::{sync::Lrc, SourceMap};
+
+use super::check::check_imports;
+use super::resolution::Resolution;
+use super::Graph;
+use crate::compiler::collect_diagnostics;
+use crate::module::binding_descriptor::{module_export_name_to_str, Binding, ModuleBinding};
+use crate::module::config::Config;
+
+/// The resolution of the imports and exports of every module in a graph, by the key of the
+/// module (see [`bundle`](crate::bundle)).
+///
+/// A runtime can bind imports to the exports of other records without resolving re-exports and
+/// expanding `export *` at startup. Names that depend on modules outside of the graph are not in
+/// the table and have to be resolved by the runtime.
+pub type LinkTable = BTreeMap<String, ModuleLinks>;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ModuleLinks {
+    /// By the local name of the import, the `as` of the import binding in the record.
+    pub imports: BTreeMap<String, Link>,
+    /// By the export name, including the names of `export *`.
+    pub exports: BTreeMap<String, Link>,
+}
+
+/// An export of the record that defines the binding.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Link {
+    /// The key of the module.
+    pub module: String,
+    /// The export name in `module`, absent for the namespace object of `module`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub export: Option<String>,
+}
+
+/// Compile `entry` and the modules it imports and resolve every import binding to the module and
+/// the export name that define it, following re-exports and `export *`.
+///
+/// Link errors are reported like [`check_graph`](crate::check_graph).
+pub fn link_graph(entry: &str, config: Config) -> Result<LinkTable, Vec<String>> {
+    let cm: Lrc<SourceMap> = Default::default();
+    let comments = SingleThreadedComments::default();
+    let mut errors = vec![];
+    let result = collect_diagnostics(&cm, |handler| {
+        let graph = Graph::load(
+            &cm,
+            &comments,
+            handler,
+            Path::new(entry),
+            |_| config.clone(),
+            &mut errors,
+        );
+        check_imports(&graph);
+        Some(graph.link_table())
+    });
+    match result {
+        Ok(table) if errors.is_empty() => Ok(table),
+        Ok(_) => Err(errors),
+        Err(diagnostics) => Err(errors.into_iter().chain(diagnostics).collect()),
+    }
+}
+
+impl Graph {
+    fn link_table(&self) -> LinkTable {
+        (0..self.modules.len())
+            .map(|module| {
+                let links = ModuleLinks {
+                    imports: self.import_links(module),
+                    exports: self
+                        .exported_names(module, &mut HashSet::new())
+                        .into_iter()
+                        .filter_map(|name| {
+                            let link = self.link(module, &name)?;
+                            Some((name, link))
+                        })
+                        .collect(),
+                };
+                (self.modules[module].key.clone(), links)
+            })
+            .collect()
+    }
+    fn import_links(&self, module: usize) -> BTreeMap<String, Link> {
+        self.modules[module]
+            .bindings
+            .iter()
+            .filter_map(|binding| {
+                let Binding::Import(import) = binding else {
+                    return None;
+                };
+                let alias = import.alias.as_ref()?.sym.to_string();
+                let target = self.resolve(module, &import.from.value)?;
+                let link = match &import.import {
+                    ModuleBinding::ModuleExportName(name) => {
+                        self.link(target, &module_export_name_to_str(name))?
+                    }
+                    ModuleBinding::Namespace => Link {
+                        module: self.modules[target].key.clone(),
+                        export: None,
+                    },
+                };
+                Some((alias, link))
+            })
+            .collect()
+    }
+    /// The export that defines `name` of `module`.
+    fn link(&self, module: usize, name: &str) -> Option<Link> {
+        match self.resolve_export(module, name, &mut HashSet::new()) {
+            Resolution::Found(export) => Some(Link {
+                module: self.modules[export.module].key.clone(),
+                export: (export.local != "*").then_some(export.name),
+            }),
+            _ => None,
+        }
+    }
+}
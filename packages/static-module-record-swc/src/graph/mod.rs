@@ -0,0 +1,189 @@
+/// Report link errors of the module graph at build time.
+mod check;
+/// Resolve the imports and exports of the module graph at build time.
+mod link;
+/// ResolveExport and GetExportedNames of the specification over the graph.
+mod resolution;
+
+pub use check::check_graph;
+pub use link::{link_graph, Link, LinkTable, ModuleLinks};
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs::read;
+use std::path::{Path, PathBuf};
+
+use swc_core::common::comments::SingleThreadedComments;
+use swc_core::common::errors::{DiagnosticBuilder, Emitter, Handler, HANDLER};
+use swc_core::common::{sync::Lrc, FileName, SourceMap, Span};
+use swc_core::ecma::ast::Module;
+
+use crate::compiler::{transform, Source};
+use crate::module::binding_descriptor::Binding;
+use crate::module::config::Config;
+
+/// An entry and the modules it imports, compiled in the same `SourceMap`.
+pub(crate) struct Graph {
+    /// In the order they are found from the entry, the entry is the first module.
+    pub modules: Vec<GraphModule>,
+    index: HashMap<String, usize>,
+}
+
+pub(crate) struct GraphModule {
+    /// The specifier resolved against the directory of the entry, like the URL path `/lib/a.js`.
+    pub key: String,
+    /// The start of the file, for diagnostics about the whole module.
+    pub span: Span,
+    pub module: Module,
+    pub bindings: Vec<Binding>,
+}
+
+impl Graph {
+    /// Compile `entry` and the modules it imports with the config returned by `config` for their
+    /// keys. Errors that are not diagnostics, like missing files, are pushed to `errors`.
+    ///
+    /// The graph follows the `from` of the bindings and the string literals passed to `import()`.
+    /// With `expandStarExports` in the config of the entry, the graph is compiled twice, the
+    /// second time with the names of `export *` found by the first.
+    pub(crate) fn load(
+        cm: &Lrc<SourceMap>,
+        comments: &SingleThreadedComments,
+        handler: &Handler,
+        entry: &Path,
+        config: impl Fn(&str) -> Config,
+        errors: &mut Vec<String>,
+    ) -> Graph {
+        let expand_star_exports = entry.file_name().is_some_and(|name| {
+            config(&format!("/{}", name.to_string_lossy())).expand_star_exports
+        });
+        let mut star_exports = HashMap::new();
+        if expand_star_exports {
+            // The diagnostics are reported by the second compilation.
+            let discard = Handler::with_emitter(false, false, Box::new(Discard));
+            let graph = HANDLER.set(&discard, || {
+                Self::load_with(
+                    cm,
+                    comments,
+                    &discard,
+                    entry,
+                    &config,
+                    &star_exports,
+                    &mut vec![],
+                )
+            });
+            star_exports = graph.star_expansions();
+        }
+        Self::load_with(cm, comments, handler, entry, &config, &star_exports, errors)
+    }
+    fn load_with(
+        cm: &Lrc<SourceMap>,
+        comments: &SingleThreadedComments,
+        handler: &Handler,
+        entry: &Path,
+        config: impl Fn(&str) -> Config,
+        star_exports: &HashMap<String, HashMap<String, Vec<String>>>,
+        errors: &mut Vec<String>,
+    ) -> Graph {
+        let mut graph = Graph {
+            modules: vec![],
+            index: HashMap::new(),
+        };
+        let root = entry.parent().unwrap_or(Path::new(""));
+        let Some(entry_key) = entry.file_name() else {
+            errors.push(format!("{}: error: not a file", entry.display()));
+            return graph;
+        };
+        let mut queue = VecDeque::from([format!("/{}", entry_key.to_string_lossy())]);
+        let mut visited = HashSet::new();
+        while let Some(key) = queue.pop_front() {
+            if !visited.insert(key.clone()) {
+                continue;
+            }
+            let path = root.join(&key[1..]);
+            let file_name = path.to_string_lossy();
+            let bytes = match read(&path) {
+                Ok(bytes) => bytes,
+                Err(err) => {
+                    errors.push(format!("{}: error: {}", file_name, err));
+                    continue;
+                }
+            };
+            let config = config(&key);
+            let source = match Source::read(&bytes, &file_name, &config) {
+                Ok(source) => source,
+                Err(err) => {
+                    errors.extend(err);
+                    continue;
+                }
+            };
+            let (source, parse) = source.parser();
+            let fm = cm.new_source_file(FileName::Real(PathBuf::from(&*file_name)), source.into());
+            let star_exports = star_exports.get(&key).cloned().unwrap_or_default();
+            let Some((module, transformer)) =
+                transform(&fm, &bytes, comments, config, star_exports, handler, parse)
+            else {
+                continue;
+            };
+            for specifier in &transformer.manifest().imports {
+                match resolve(&key, specifier) {
+                    Some(key) => queue.push_back(key),
+                    None if is_path(specifier) => errors.push(format!(
+                        "{}: error: `{}` is outside the directory of the entry.",
+                        file_name, specifier
+                    )),
+                    None => {}
+                }
+            }
+            graph.index.insert(key.clone(), graph.modules.len());
+            graph.modules.push(GraphModule {
+                key,
+                span: Span::new(fm.start_pos, fm.start_pos, Default::default()),
+                module,
+                bindings: transformer.bindings().to_vec(),
+            });
+        }
+        graph
+    }
+    /// The index of the module imported by `specifier` from the module at `referrer`, `None` if
+    /// it is left to the host, above the root or failed to compile.
+    pub(crate) fn resolve(&self, referrer: usize, specifier: &str) -> Option<usize> {
+        let key = resolve(&self.modules[referrer].key, specifier)?;
+        self.index.get(&key).copied()
+    }
+}
+
+/// Resolve `specifier` against `referrer` like the path of a URL. `None` for specifiers that
+/// are not paths and for paths above the root.
+///
+/// Only relative (`./`, `../`) and absolute (`/`) specifiers are resolved.
+fn resolve(referrer: &str, specifier: &str) -> Option<String> {
+    let mut segments: Vec<&str> = if specifier.starts_with('/') {
+        vec![]
+    } else if is_path(specifier) {
+        let mut segments: Vec<&str> = referrer.split('/').skip(1).collect();
+        segments.pop();
+        segments
+    } else {
+        return None;
+    };
+    for segment in specifier.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop()?;
+            }
+            segment => segments.push(segment),
+        }
+    }
+    Some(format!("/{}", segments.join("/")))
+}
+
+/// Relative and absolute specifiers, the others are left to the host.
+fn is_path(specifier: &str) -> bool {
+    specifier.starts_with('/') || specifier.starts_with("./") || specifier.starts_with("../")
+}
+
+struct Discard;
+
+impl Emitter for Discard {
+    fn emit(&mut self, _: &DiagnosticBuilder<'_>) {}
+}
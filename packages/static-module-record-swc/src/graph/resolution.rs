@@ -0,0 +1,228 @@
+use std::collections::{HashMap, HashSet};
+
+use swc_core::common::Span;
+use swc_core::ecma::ast::{ModuleExportName, Str};
+
+use super::Graph;
+use crate::module::binding_descriptor::{module_export_name_to_str, Binding, ModuleBinding};
+
+/// A local binding of a module, `*` is the namespace object of the module.
+#[derive(Clone)]
+pub(super) struct Export {
+    pub module: usize,
+    /// The name of the binding in `module`, or `*`.
+    pub local: String,
+    /// The export name of the binding in `module`, or `*`.
+    pub name: String,
+    pub span: Span,
+}
+
+/// ResolveExport of the specification.
+pub(super) enum Resolution {
+    Found(Export),
+    /// Circular re-exports are not found either.
+    NotFound,
+    Ambiguous(Export, Export),
+    /// It depends on a module outside of the graph.
+    Unknown,
+}
+
+impl Graph {
+    pub(super) fn resolve_export(
+        &self,
+        module: usize,
+        name: &str,
+        visited: &mut HashSet<(usize, String)>,
+    ) -> Resolution {
+        if !visited.insert((module, name.to_string())) {
+            return Resolution::NotFound;
+        }
+        for binding in &self.modules[module].bindings {
+            let Binding::Export(export) = binding else {
+                continue;
+            };
+            match (&export.export, &export.from) {
+                (ModuleBinding::ModuleExportName(local), None) => {
+                    let exported = export.alias.as_ref().unwrap_or(local);
+                    if module_export_name_to_str(exported) == name {
+                        return Resolution::Found(Export {
+                            module,
+                            local: module_export_name_to_str(local),
+                            name: name.to_string(),
+                            span: self.span_of(module, exported),
+                        });
+                    }
+                }
+                (ModuleBinding::ModuleExportName(import), Some(from)) => {
+                    let exported = export.alias.as_ref().unwrap_or(import);
+                    if module_export_name_to_str(exported) == name {
+                        return match self.resolve(module, &from.value) {
+                            Some(target) => self.resolve_export(
+                                target,
+                                &module_export_name_to_str(import),
+                                visited,
+                            ),
+                            None => Resolution::Unknown,
+                        };
+                    }
+                }
+                // export * as ns from 'mod'
+                (ModuleBinding::Namespace, Some(from)) => match &export.alias {
+                    Some(alias) if module_export_name_to_str(alias) == name => {
+                        return match self.resolve(module, &from.value) {
+                            Some(target) => Resolution::Found(Export {
+                                module: target,
+                                local: "*".to_string(),
+                                name: "*".to_string(),
+                                span: self.span_of(module, alias),
+                            }),
+                            None => Resolution::Unknown,
+                        };
+                    }
+                    _ => {}
+                },
+                _ => {}
+            }
+        }
+        if name == "default" {
+            return Resolution::NotFound;
+        }
+        let mut star_resolution = Resolution::NotFound;
+        for from in self.star_exports(module) {
+            let Some(target) = self.resolve(module, &from.value) else {
+                return Resolution::Unknown;
+            };
+            match self.resolve_export(target, name, visited) {
+                Resolution::Found(export) => match &star_resolution {
+                    Resolution::Found(found)
+                        if found.module != export.module || found.local != export.local =>
+                    {
+                        return Resolution::Ambiguous(found.clone(), export);
+                    }
+                    Resolution::Found(_) => {}
+                    _ => star_resolution = Resolution::Found(export),
+                },
+                Resolution::NotFound => {}
+                resolution => return resolution,
+            }
+        }
+        star_resolution
+    }
+    /// GetExportedNames of the specification, modules outside of the graph have no names.
+    pub(super) fn exported_names(
+        &self,
+        module: usize,
+        visited: &mut HashSet<usize>,
+    ) -> Vec<String> {
+        if !visited.insert(module) {
+            return vec![];
+        }
+        let mut names = self.explicit_exports(module);
+        for from in self.star_exports(module) {
+            let Some(target) = self.resolve(module, &from.value) else {
+                continue;
+            };
+            for name in self.exported_names(target, visited) {
+                if name != "default" && !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+        }
+        names
+    }
+    pub(super) fn explicit_exports(&self, module: usize) -> Vec<String> {
+        self.modules[module]
+            .bindings
+            .iter()
+            .filter_map(|binding| match binding {
+                Binding::Export(export) => match (&export.export, &export.alias) {
+                    (_, Some(alias)) => Some(module_export_name_to_str(alias)),
+                    (ModuleBinding::ModuleExportName(name), None) => {
+                        Some(module_export_name_to_str(name))
+                    }
+                    _ => None,
+                },
+                Binding::Import(_) => None,
+            })
+            .collect()
+    }
+    /// The `from` of `export * from 'mod'`.
+    pub(super) fn star_exports(&self, module: usize) -> impl Iterator<Item = &Str> {
+        self.modules[module]
+            .bindings
+            .iter()
+            .filter_map(|binding| match binding {
+                Binding::Export(export)
+                    if export.export == ModuleBinding::Namespace && export.alias.is_none() =>
+                {
+                    export.from.as_ref()
+                }
+                _ => None,
+            })
+    }
+    /// Synthesized names like the `default` of `export default expr` are at the start of the file.
+    pub(super) fn span_of(&self, module: usize, name: &ModuleExportName) -> Span {
+        let span = match name {
+            ModuleExportName::Ident(ident) => ident.span,
+            ModuleExportName::Str(str) => str.span,
+        };
+        if span.is_dummy() {
+            self.modules[module].span
+        } else {
+            span
+        }
+    }
+    /// The names of each `export * from` of every module, by the key of the module and the
+    /// specifier. A name goes to the first `export *` that provides it, ambiguous names are
+    /// excluded. Either all or none of the `export *` of a module are expanded, modules whose
+    /// `export *` depend on modules outside of the graph are not.
+    pub(super) fn star_expansions(&self) -> HashMap<String, HashMap<String, Vec<String>>> {
+        let mut expansions = HashMap::new();
+        'modules: for module in 0..self.modules.len() {
+            let stars: Vec<&Str> = self.star_exports(module).collect();
+            if stars.is_empty() || self.has_unknown_exports(module, &mut HashSet::new()) {
+                continue;
+            }
+            let explicit = self.explicit_exports(module);
+            let mut names: HashMap<String, Vec<String>> = stars
+                .iter()
+                .map(|from| (from.value.to_string(), vec![]))
+                .collect();
+            for name in self.exported_names(module, &mut HashSet::new()) {
+                if explicit.contains(&name) {
+                    continue;
+                }
+                match self.resolve_export(module, &name, &mut HashSet::new()) {
+                    Resolution::Found(_) => {}
+                    Resolution::NotFound | Resolution::Ambiguous(..) => continue,
+                    // Re-exported from outside of the graph, leave it to the runtime.
+                    Resolution::Unknown => continue 'modules,
+                }
+                let star = stars.iter().find(|from| {
+                    self.resolve(module, &from.value).is_some_and(|target| {
+                        matches!(
+                            self.resolve_export(target, &name, &mut HashSet::new()),
+                            Resolution::Found(_)
+                        )
+                    })
+                });
+                if let Some(from) = star {
+                    names.get_mut(&*from.value).unwrap().push(name);
+                }
+            }
+            expansions.insert(self.modules[module].key.clone(), names);
+        }
+        expansions
+    }
+    /// `export *` of `module` or the modules it re-exports lead outside of the graph.
+    fn has_unknown_exports(&self, module: usize, visited: &mut HashSet<usize>) -> bool {
+        if !visited.insert(module) {
+            return false;
+        }
+        self.star_exports(module)
+            .any(|from| match self.resolve(module, &from.value) {
+                Some(target) => self.has_unknown_exports(target, visited),
+                None => true,
+            })
+    }
+}
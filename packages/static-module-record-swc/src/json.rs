@@ -0,0 +1,128 @@
+use serde::de::IgnoredAny;
+use swc_core::common::comments::Comments;
+use swc_core::common::{BytePos, FileName, SourceFile, Span, Spanned, DUMMY_SP};
+use swc_core::ecma::ast::*;
+use swc_core::ecma::parser::{parse_file_as_expr, Syntax};
+
+pub const NOT_A_JSON_VALUE: &str = "A JSON module must contain a single JSON value.";
+
+/// Convert the source of a `.json` file, which starts at `start_pos`, into
+/// `export default <value>`.
+///
+/// @swc/core parses a `.json` file as JavaScript, where a top-level object is a block statement,
+/// so the plugin compiles the source text instead of the program.
+pub fn json_program(source: String, start_pos: BytePos) -> Result<Module, Span> {
+    let fm = SourceFile::new(FileName::Anon, false, FileName::Anon, source, start_pos);
+    parse_json_module(&fm, None)
+}
+
+/// Parse `fm` into `export default <value>`.
+///
+/// The error is the span of the first part that is not JSON.
+pub fn parse_json_module(fm: &SourceFile, comments: Option<&dyn Comments>) -> Result<Module, Span> {
+    // JSON is stricter than JavaScript literals: no single quotes, comments, hex numbers...
+    if let Err(err) = serde_json::from_str::<IgnoredAny>(&fm.src) {
+        let pos = fm.start_pos + BytePos(offset_of(&fm.src, err.line(), err.column()) as u32);
+        return Err(Span::new(pos, pos, Default::default()));
+    }
+    let value = parse_file_as_expr(
+        fm,
+        Syntax::Es(Default::default()),
+        EsVersion::latest(),
+        comments,
+        &mut vec![],
+    )
+    .map_err(|err| err.span())?;
+    json_module(value)
+}
+
+/// The byte offset of the 1-based `line` and `column` of serde_json.
+fn offset_of(source: &str, line: usize, column: usize) -> usize {
+    let line_start: usize = source
+        .split('\n')
+        .take(line.saturating_sub(1))
+        .map(|line| line.len() + 1)
+        .sum();
+    (line_start + column.saturating_sub(1)).min(source.len())
+}
+
+/// Convert a JSON value into `export default <value>`.
+///
+/// The error is the span of the first part that is not JSON.
+pub fn json_module(value: Box<Expr>) -> Result<Module, Span> {
+    let span = value.span();
+    let value = json_value(*value)?;
+    Ok(Module {
+        span,
+        body: vec![ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultExpr(
+            ExportDefaultExpr {
+                span,
+                expr: Box::new(value),
+            },
+        ))],
+        shebang: None,
+    })
+}
+
+fn json_value(value: Expr) -> Result<Expr, Span> {
+    match value {
+        Expr::Lit(Lit::Str(_) | Lit::Num(_) | Lit::Bool(_) | Lit::Null(_)) => Ok(value),
+        Expr::Unary(UnaryExpr {
+            op: op!(unary, "-"),
+            ref arg,
+            ..
+        }) if matches!(**arg, Expr::Lit(Lit::Num(_))) => Ok(value),
+        Expr::Array(array) => Ok(ArrayLit {
+            elems: array
+                .elems
+                .into_iter()
+                .map(|elem| match elem {
+                    Some(ExprOrSpread { spread: None, expr }) => Ok(Some(ExprOrSpread {
+                        spread: None,
+                        expr: Box::new(json_value(*expr)?),
+                    })),
+                    elem => Err(elem.map_or(array.span, |elem| elem.span())),
+                })
+                .collect::<Result<_, _>>()?,
+            ..array
+        }
+        .into()),
+        Expr::Object(object) => Ok(ObjectLit {
+            props: object
+                .props
+                .into_iter()
+                .map(|prop| match prop {
+                    PropOrSpread::Prop(prop) => match *prop {
+                        Prop::KeyValue(KeyValueProp {
+                            key: PropName::Str(key),
+                            value,
+                        }) => Ok(PropOrSpread::Prop(Box::new(
+                            KeyValueProp {
+                                key: json_key(key),
+                                value: Box::new(json_value(*value)?),
+                            }
+                            .into(),
+                        ))),
+                        prop => Err(prop.span()),
+                    },
+                    PropOrSpread::Spread(spread) => Err(spread.dot3_token),
+                })
+                .collect::<Result<_, _>>()?,
+            ..object
+        }
+        .into()),
+        value => Err(value.span()),
+    }
+}
+
+/// `{ "__proto__": 1 }` sets the prototype in JavaScript but is an own property in JSON.
+fn json_key(key: Str) -> PropName {
+    if &*key.value == "__proto__" {
+        PropName::Computed(ComputedPropName {
+            span: DUMMY_SP,
+            expr: Box::new(key.into()),
+        })
+    } else {
+        PropName::Str(key)
+    }
+}
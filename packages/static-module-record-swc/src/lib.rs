@@ -1,8 +1,25 @@
 #![allow(clippy::not_unsafe_ptr_arg_deref)]
 
-use module::{config::Config, VirtualModuleRecordTransformer};
-use script::ErrorTransformer;
-use swc_core::common::DUMMY_SP;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+pub use bundle::{bundle, Bundle};
+pub use compiler::{
+    compile_asset, compile_file, compile_file_with_manifest, compile_json, compile_module,
+    compile_script, compile_script_with_manifest, compile_wasm,
+};
+pub use graph::{check_graph, link_graph, Link, LinkTable, ModuleLinks};
+use json::{json_program, NOT_A_JSON_VALUE};
+pub use manifest::Manifest;
+pub use module::config;
+use module::{
+    config::{Config, Template},
+    VirtualModuleRecordTransformer,
+};
+use script::{script_to_module, ErrorTransformer};
+use swc_core::common::{
+    comments::Comments, errors::SourceMapper, util::take::Take, Mark, Spanned, DUMMY_SP,
+};
 use swc_core::ecma::ast::*;
 use swc_core::ecma::visit::FoldWith;
 use swc_core::plugin::{
@@ -11,40 +28,115 @@ use swc_core::plugin::{
 };
 use utils::emit_error;
 
+mod asset;
+mod bundle;
+mod compiler;
+mod graph;
+mod json;
+mod manifest;
 mod module;
 mod script;
 mod utils;
+mod wasm;
 
 #[cfg(test)]
 mod test;
 
+pub(crate) const SPLIT_NEEDS_MANIFEST: &str = "The split template needs the manifest, which the \
+    @swc/core plugin can not write. Use `compile_file_with_manifest` or `--manifest` of the command \
+    line.";
+
+pub(crate) const HASH_NEEDS_SOURCE: &str =
+    "`hash` needs the source text, which @swc/core did not provide for this file.";
+
 #[plugin_transform]
 pub fn process_transform(program: Program, metadata: TransformPluginProgramMetadata) -> Program {
-    let config =
-        serde_json::from_str::<Config>(&metadata.get_transform_plugin_config().unwrap_or_default());
-    let filename = metadata.get_context(&TransformPluginMetadataContextKind::Filename);
-    match config {
-        Ok(config) => match &program {
-            Program::Script(script) => {
-                emit_error(
-                    script.span,
-                    "VirtualModuleRecord transformer must run in the Module mode.",
-                );
-                program.fold_with(&mut ErrorTransformer {
-                    msg: "VirtualModuleRecord transformer must run in the Module mode.".to_string(),
-                })
+    let source = metadata.source_map.span_to_snippet(program.span()).ok();
+    transform_program(
+        program,
+        &metadata.get_transform_plugin_config().unwrap_or_default(),
+        metadata.get_context(&TransformPluginMetadataContextKind::Filename),
+        source,
+        metadata.comments,
+        metadata.unresolved_mark,
+    )
+}
+
+/// The plugin with the metadata of @swc/core, `source` is the text of `program`.
+pub(crate) fn transform_program(
+    program: Program,
+    config: &str,
+    filename: Option<String>,
+    source: Option<String>,
+    comments: Option<impl Comments + 'static>,
+    unresolved_mark: Mark,
+) -> Program {
+    let config = plugin_config(config);
+    // JSON modules share the templates of JavaScript modules: `export default <value>`.
+    let program = if filename
+        .as_ref()
+        .is_some_and(|name| name.ends_with(".json"))
+    {
+        let span = program.span();
+        let module = source
+            .clone()
+            .ok_or(span)
+            .and_then(|source| json_program(source, span.lo));
+        match module {
+            Ok(module) => Program::Module(module),
+            Err(span) => {
+                emit_error(span, NOT_A_JSON_VALUE);
+                return Program::Module(Module::dummy()).fold_with(&mut ErrorTransformer {
+                    msg: NOT_A_JSON_VALUE.to_string(),
+                });
             }
-            Program::Module(_) => program.fold_with(&mut VirtualModuleRecordTransformer::new(
-                config,
-                filename,
-                metadata.unresolved_mark,
-            )),
-        },
-        Err(err) => {
-            emit_error(DUMMY_SP, &format!("{}", err));
+        }
+    } else {
+        program
+    };
+    match config {
+        // The plugin can only return the program, the metadata of the split record would be lost.
+        Ok(Config {
+            template: Template::Split { .. },
+            ..
+        }) => {
+            emit_error(DUMMY_SP, SPLIT_NEEDS_MANIFEST);
             program.fold_with(&mut ErrorTransformer {
-                msg: format!("{}", err),
+                msg: SPLIT_NEEDS_MANIFEST.to_string(),
+            })
+        }
+        Ok(config) => {
+            let module = match program {
+                // A classic script (`isModule: false`) runs in the global scope of the compartment.
+                Program::Script(script) => script_to_module(script, unresolved_mark),
+                Program::Module(module) => module,
+            };
+            let mut transformer =
+                VirtualModuleRecordTransformer::new(config, filename, unresolved_mark);
+            match &source {
+                Some(source) => transformer = transformer.with_source(source),
+                None if transformer.config.hash => emit_error(module.span, HASH_NEEDS_SOURCE),
+                None => {}
+            }
+            Program::Module(module).fold_with(&mut match comments {
+                Some(comments) => transformer.with_comments(comments),
+                None => transformer,
             })
         }
+        Err(err) => {
+            emit_error(DUMMY_SP, &err);
+            program.fold_with(&mut ErrorTransformer { msg: err })
+        }
     }
 }
+
+/// @swc/core passes the config of the plugin as a string for every file. Configs are cached by
+/// that string, so the files compiled by the same plugin instance share the parsed custom template.
+pub(crate) fn plugin_config(config: &str) -> Result<Config, String> {
+    static CONFIGS: OnceLock<Mutex<HashMap<String, Result<Config, String>>>> = OnceLock::new();
+    let mut configs = CONFIGS.get_or_init(Default::default).lock().unwrap();
+    configs
+        .entry(config.to_string())
+        .or_insert_with(|| serde_json::from_str::<Config>(config).map_err(|err| err.to_string()))
+        .clone()
+}
@@ -0,0 +1,20 @@
+use serde::Serialize;
+
+/// What a host can know about a record without running it.
+///
+/// The command line writes it next to the record with `--manifest`.
+#[derive(Debug, Clone, Serialize)]
+pub struct Manifest {
+    /// `false` when `execute` only declares and exports bindings, the host can skip it until an
+    /// export is read. The record has the same `sideEffects: false` hint.
+    #[serde(rename = "sideEffects")]
+    pub side_effects: bool,
+    /// The specifiers requested by the module: the `from` of its bindings, then the string
+    /// literals passed to `import()`, without duplicates.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub imports: Vec<String>,
+    /// The record without `execute` with the `split` template, `execute` is registered by the
+    /// script.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub record: Option<serde_json::Value>,
+}
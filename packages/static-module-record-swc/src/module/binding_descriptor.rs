@@ -1,4 +1,7 @@
+use std::collections::HashMap;
+
 use crate::utils::*;
+use smallvec::SmallVec;
 use swc_core::common::DUMMY_SP;
 use swc_core::ecma::ast::*;
 use swc_core::ecma::utils::quote_ident;
@@ -48,7 +51,7 @@ impl ModuleBinding {
     }
 }
 
-fn module_export_name_to_str(binding: &ModuleExportName) -> String {
+pub fn module_export_name_to_str(binding: &ModuleExportName) -> String {
     match binding {
         ModuleExportName::Ident(ident) => ident.to_id().0.to_string(),
         ModuleExportName::Str(str) => str.value.to_string(),
@@ -210,21 +213,8 @@ impl Binding {
     }
 }
 
-pub struct LiveExportTracingBinding {
-    pub local_ident: Ident,
-    pub export: ModuleExportName,
-}
-impl LiveExportTracingBinding {
-    pub fn simple(id: &Ident) -> LiveExportTracingBinding {
-        LiveExportTracingBinding {
-            local_ident: id.clone(),
-            export: ModuleExportName::Ident(id.clone()),
-        }
-    }
-    pub fn complex(id: &Ident, export: &ModuleExportName) -> LiveExportTracingBinding {
-        LiveExportTracingBinding {
-            local_ident: id.clone(),
-            export: export.clone(),
-        }
-    }
-}
+/// The export names of every exported local binding, indexed by the binding.
+///
+/// Most bindings are exported once, a module with thousands of exports should not pay a linear
+/// scan for every declaration, update or assignment.
+pub type LiveExportTracingBindings = HashMap<Id, SmallVec<[ModuleExportName; 1]>>;
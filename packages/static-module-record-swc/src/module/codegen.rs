@@ -1,13 +1,33 @@
-use super::{binding_descriptor::Binding, config::Template, VirtualModuleRecordTransformer};
+use std::collections::HashMap;
+
+use super::{
+    binding_descriptor::Binding,
+    config::{Protocol, Template},
+    template::custom_template,
+    VirtualModuleRecordTransformer,
+};
 use crate::utils::*;
+use serde_json::{Map, Value};
 use swc_core::common::util::take::Take;
 use swc_core::common::DUMMY_SP;
 use swc_core::ecma::ast::*;
 use swc_core::ecma::utils::{quote_ident, ExprFactory};
 
 impl VirtualModuleRecordTransformer {
-    pub fn codegen(&self, stmt: Vec<Stmt>, transformer: &VirtualModuleRecordTransformer) -> Module {
-        let expr = self.virtual_module_record(stmt, transformer);
+    pub fn codegen(&mut self, stmt: Vec<Stmt>) -> Module {
+        let mut expr = self.virtual_module_record(stmt);
+        if let Template::Split { .. } = self.config.template {
+            if self.config.protocol == Protocol::Endo {
+                emit_error(
+                    DUMMY_SP,
+                    "The split template does not support the endo protocol.",
+                );
+                return Module::dummy();
+            }
+            let (execute, metadata) = split_record(expr, self.config.protocol.execute_key());
+            self.record_metadata = Some(metadata);
+            expr = execute;
+        }
         Module {
             body: match &self.config.template {
                 Template::ExportDefault => export_default_expr(expr),
@@ -25,15 +45,47 @@ impl VirtualModuleRecordTransformer {
                     Expr::Lit(relative(self.file_name.as_ref().unwrap(), cwd).into()),
                     expr,
                 ),
+                Template::Split { callback_name, cwd } => match self.specifier(cwd) {
+                    Some(key) => callback(
+                        Ident::new(callback_name.clone().into(), DUMMY_SP),
+                        Expr::Lit(key.into()),
+                        expr,
+                    ),
+                    None => {
+                        emit_error(DUMMY_SP, "The split template needs the file name.");
+                        vec![]
+                    }
+                },
+                Template::Custom {
+                    template,
+                    cwd,
+                    parsed,
+                } => {
+                    let values = HashMap::from([
+                        ("$SPECIFIER", self.specifier(cwd)),
+                        ("$FILENAME", self.file_name.clone()),
+                        ("$HASH", self.hash.clone()),
+                    ]);
+                    let items = parsed
+                        .get_or_parse(template)
+                        .and_then(|template| custom_template(template, expr, values));
+                    items.unwrap_or_else(|err| {
+                        emit_error(DUMMY_SP, &err);
+                        vec![]
+                    })
+                }
             },
             ..Module::dummy()
         }
     }
-    fn virtual_module_record(
-        &self,
-        mut stmts: Vec<Stmt>,
-        transformer: &VirtualModuleRecordTransformer,
-    ) -> Expr {
+    /// The path from `cwd` when it is given, otherwise the file name.
+    fn specifier(&self, cwd: &Option<String>) -> Option<String> {
+        self.file_name.as_ref().map(|file_name| match cwd {
+            Some(cwd) => relative(file_name, cwd),
+            None => file_name.clone(),
+        })
+    }
+    fn virtual_module_record(&self, mut stmts: Vec<Stmt>) -> Expr {
         if self.uses_global_lookup {
             stmts.insert(
                 0,
@@ -53,24 +105,10 @@ impl VirtualModuleRecordTransformer {
                 }))),
             );
         }
-        let init_fn = Function {
-            is_async: self.uses_top_level_await,
-            body: Some(BlockStmt {
-                span: DUMMY_SP,
-                stmts,
-            }),
-            params: {
-                let emit_import_context =
-                    self.uses_import_meta || self.uses_dynamic_import || self.uses_global_lookup;
-
-                let mut result = vec![param(transformer.module_env_record_ident.clone())];
-                if emit_import_context {
-                    result.push(param(transformer.import_context_ident.clone()));
-                }
-                result
-            },
-            ..Function::dummy()
-        };
+        if self.config.protocol == Protocol::Endo {
+            return self.endo_module_record(stmts);
+        }
+        let init_fn = self.execute(stmts);
 
         let mut props = vec![];
 
@@ -98,8 +136,27 @@ impl VirtualModuleRecordTransformer {
             props.push(key_value("needsImport".into(), t.into()));
         }
 
+        if self.uses_direct_eval {
+            props.push(key_value("needsEvaluate".into(), t.into()));
+        }
+
+        if self.side_effect_free {
+            props.push(key_value(
+                "sideEffects".into(),
+                Bool {
+                    span: DUMMY_SP,
+                    value: false,
+                }
+                .into(),
+            ));
+        }
+
+        if let Some(hash) = self.hash.as_ref().filter(|_| self.config.hash) {
+            props.push(key_value("hash".into(), hash.as_str().into()));
+        }
+
         props.push(key_value(
-            "execute".into(),
+            self.config.protocol.execute_key().into(),
             FnExpr {
                 ident: None,
                 function: Box::new(init_fn),
@@ -113,6 +170,88 @@ impl VirtualModuleRecordTransformer {
         }
         .into()
     }
+    /// function (__, context) { ...stmts }
+    pub fn execute(&self, stmts: Vec<Stmt>) -> Function {
+        Function {
+            is_async: self.uses_top_level_await,
+            body: Some(BlockStmt {
+                span: DUMMY_SP,
+                stmts,
+            }),
+            params: {
+                let emit_import_context = self.uses_import_meta
+                    || self.uses_dynamic_import
+                    || self.uses_global_lookup
+                    || self.uses_direct_eval;
+
+                let mut result = vec![param(self.module_env_record_ident.clone())];
+                if emit_import_context {
+                    result.push(param(self.import_context_ident.clone()));
+                }
+                result
+            },
+            ..Function::dummy()
+        }
+    }
+}
+
+/// Take the `execute` function out of the record, the rest of the record is serializable.
+fn split_record(record: Expr, execute_key: &str) -> (Expr, Value) {
+    let Expr::Object(record) = record else {
+        unreachable!("the record is an object literal")
+    };
+    let mut execute = None;
+    let mut metadata = Map::new();
+    for prop in record.props {
+        let PropOrSpread::Prop(prop) = prop else {
+            unreachable!("the record has no spread")
+        };
+        let Prop::KeyValue(KeyValueProp {
+            key: PropName::Ident(key),
+            value,
+        }) = *prop
+        else {
+            unreachable!("the record only has key-value properties")
+        };
+        if &*key.sym == execute_key {
+            execute = Some(*value);
+        } else {
+            metadata.insert(key.sym.to_string(), json_value(&value));
+        }
+    }
+    (
+        execute.expect("the record has an execute function"),
+        Value::Object(metadata),
+    )
+}
+
+fn json_value(expr: &Expr) -> Value {
+    match expr {
+        Expr::Lit(Lit::Str(str)) => Value::String(str.value.to_string()),
+        Expr::Lit(Lit::Bool(bool)) => Value::Bool(bool.value),
+        Expr::Array(array) => Value::Array(
+            array
+                .elems
+                .iter()
+                .flatten()
+                .map(|elem| json_value(&elem.expr))
+                .collect(),
+        ),
+        Expr::Object(object) => Value::Object(
+            object
+                .props
+                .iter()
+                .filter_map(|prop| match prop.as_prop()?.as_key_value()? {
+                    KeyValueProp {
+                        key: PropName::Ident(key),
+                        value,
+                    } => Some((key.sym.to_string(), json_value(value))),
+                    _ => None,
+                })
+                .collect(),
+        ),
+        _ => unreachable!("the metadata of the record is made of strings and booleans"),
+    }
 }
 
 fn export_default_expr(expr: Expr) -> Vec<ModuleItem> {
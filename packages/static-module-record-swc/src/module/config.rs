@@ -1,16 +1,43 @@
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
+
 use serde::{Deserialize, Serialize};
+use swc_core::ecma::ast::Module;
+
+use super::template::parse_template;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(default)]
 pub struct Config {
     /// The template of code generation
     pub template: Template,
+    /// The shape of the emitted record
+    pub protocol: Protocol,
+    /// Compile the files ending with these extensions into asset records, e.g. `{ ".css": "text" }`
+    pub assets: HashMap<String, AssetKind>,
+    /// Pass the bindings visible at direct `eval` calls to `context.evaluate`
+    #[serde(rename = "directEval")]
+    pub direct_eval: bool,
+    /// Replace `export * from` with a re-export of each name when the graph is compiled together
+    #[serde(rename = "expandStarExports")]
+    pub expand_star_exports: bool,
+    /// The exports used across the app by the file name of the module, other exports are removed
+    #[serde(rename = "usedExports")]
+    pub used_exports: HashMap<String, Vec<String>>,
+    /// Add the hash of the source and of this config to the record as `hash`
+    pub hash: bool,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
             template: Template::ExportDefault,
+            protocol: Protocol::default(),
+            assets: HashMap::new(),
+            direct_eval: false,
+            expand_star_exports: false,
+            used_exports: HashMap::new(),
+            hash: false,
         }
     }
 }
@@ -42,4 +69,98 @@ pub enum Template {
     /// "use strict"; ({ ... })
     #[serde(rename = "eval")]
     Eval,
+
+    /// callback_name("/path_from/cwd", function (__, context) { ... })
+    ///
+    /// The other parts of the record are in the `record` of the manifest.
+    #[serde(rename = "split")]
+    Split {
+        #[serde(rename = "callback")]
+        callback_name: String,
+        /// The key is the path from `cwd` like `callback-cwd`, otherwise the file name
+        #[serde(default)]
+        cwd: Option<String>,
+    },
+
+    /// A JavaScript module where `$RECORD` is replaced by the record, and `$SPECIFIER`,
+    /// `$FILENAME` and `$HASH` by strings, e.g. `register($SPECIFIER, $RECORD)`
+    #[serde(rename = "custom")]
+    Custom {
+        template: String,
+        /// `$SPECIFIER` is the path from `cwd` like `callback-cwd`, otherwise the file name
+        #[serde(default)]
+        cwd: Option<String>,
+        /// The parsed `template`, shared by the clones of the config
+        #[serde(skip)]
+        parsed: ParsedTemplate,
+    },
+}
+
+/// The custom template, parsed by the first record and cloned for the others.
+#[derive(Debug, Clone, Default)]
+pub struct ParsedTemplate(Arc<OnceLock<Result<Module, String>>>);
+
+impl ParsedTemplate {
+    pub(crate) fn get_or_parse(&self, template: &str) -> Result<&Module, String> {
+        self.0
+            .get_or_init(|| parse_template(template))
+            .as_ref()
+            .map_err(Clone::clone)
+    }
+    #[cfg(test)]
+    pub(crate) fn is_parsed(&self) -> bool {
+        self.0.get().is_some()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Protocol {
+    /// { bindings, execute(__, context), ... } of the 2022 VirtualModuleSource draft, which
+    /// @masknet/compartment implements
+    #[default]
+    #[serde(rename = "2022-draft")]
+    Draft2022,
+    /// { bindings, initialize(__, context), ... } of the ModuleSource draft
+    #[serde(rename = "module-source")]
+    ModuleSource,
+    /// { imports, exports, reexports, __syncModuleProgram__, ... } of SES compartments
+    #[serde(rename = "endo")]
+    Endo,
+}
+
+impl Protocol {
+    /// The name of the function that runs the module body.
+    pub fn execute_key(self) -> &'static str {
+        match self {
+            Protocol::Draft2022 | Protocol::Endo => "execute",
+            Protocol::ModuleSource => "initialize",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssetKind {
+    /// export default "file content"
+    #[serde(rename = "text")]
+    Text,
+    /// export default Uint8Array.from(atob("base64"), ...)
+    #[serde(rename = "bytes")]
+    Bytes,
+    /// export default { "className": "className_hash" }
+    #[serde(rename = "css-module")]
+    CssModule,
+    /// export default new URL("./file", import.meta.url).href
+    #[serde(rename = "url")]
+    Url,
+}
+
+impl Config {
+    /// The asset kind of `file_name`, the longest matching extension wins.
+    pub fn asset_kind(&self, file_name: &str) -> Option<AssetKind> {
+        self.assets
+            .iter()
+            .filter(|(extension, _)| file_name.ends_with(extension.as_str()))
+            .max_by_key(|(extension, _)| extension.len())
+            .map(|(_, kind)| *kind)
+    }
 }
@@ -0,0 +1,138 @@
+use std::collections::{HashMap, HashSet};
+
+use swc_core::ecma::ast::*;
+use swc_core::ecma::atoms::JsWord;
+use swc_core::ecma::utils::private_ident;
+use swc_core::ecma::visit::{noop_visit_type, Visit, VisitWith};
+
+use super::{binding_descriptor::*, VirtualModuleRecordTransformer};
+
+impl VirtualModuleRecordTransformer {
+    /// Remove the export bindings and the live export tracing of the exports that are not in the
+    /// `config.used_exports` of this module. `export *` is kept, its names are not known here.
+    ///
+    /// A module that is only re-exported by removed bindings is still imported, it has to be
+    /// evaluated in the same order.
+    pub fn drop_unused_exports(&mut self) {
+        let Some(used) = self
+            .file_name
+            .as_ref()
+            .and_then(|file_name| self.config.used_exports.get(file_name))
+        else {
+            return;
+        };
+        let is_unused = |export: &ExportBinding| {
+            let name = match (&export.export, &export.alias) {
+                (_, Some(alias)) => module_export_name_to_str(alias),
+                (ModuleBinding::ModuleExportName(name), None) => module_export_name_to_str(name),
+                // export * from 'mod'
+                _ => return None,
+            };
+            (!used.contains(&name)).then_some(name)
+        };
+        let mut loaded: HashSet<JsWord> = self
+            .bindings
+            .iter()
+            .filter_map(|binding| match binding {
+                Binding::Import(import) => Some(import.from.value.clone()),
+                Binding::Export(export) if is_unused(export).is_none() => {
+                    export.from.as_ref().map(|from| from.value.clone())
+                }
+                Binding::Export(_) => None,
+            })
+            .collect();
+        let mut unused_exports = HashSet::new();
+        let mut bindings = vec![];
+        for binding in std::mem::take(&mut self.bindings) {
+            let Binding::Export(export) = &binding else {
+                bindings.push(binding);
+                continue;
+            };
+            let Some(name) = is_unused(export) else {
+                bindings.push(binding);
+                continue;
+            };
+            unused_exports.insert(name);
+            match &export.from {
+                Some(from) if loaded.insert(from.value.clone()) => bindings.push(
+                    ImportBinding {
+                        import: ModuleBinding::Namespace,
+                        from: from.clone(),
+                        alias: Some(private_ident!(format!("reexport_{}", unused_exports.len()))),
+                    }
+                    .into(),
+                ),
+                _ => {}
+            }
+        }
+        self.bindings = bindings;
+
+        let unexported_locals = &mut self.unexported_locals;
+        self.local_resolved_bindings.retain(|local, exports| {
+            exports.retain(|export| !unused_exports.contains(&module_export_name_to_str(export)));
+            if exports.is_empty() {
+                unexported_locals.insert(local.clone());
+            }
+            !exports.is_empty()
+        });
+        self.unused_exports = unused_exports;
+    }
+    pub fn is_used_export(&self, name: &str) -> bool {
+        !self.unused_exports.contains(name)
+    }
+    /// Remove the side-effect free declarations of the locals whose exports were removed, when
+    /// nothing else refers to them.
+    pub fn drop_unused_declarations(&self, mut stmts: Vec<Stmt>) -> Vec<Stmt> {
+        if self.unexported_locals.is_empty() {
+            return stmts;
+        }
+        // Removing a declaration may leave another one without references.
+        loop {
+            let references = count_references(&stmts);
+            let len = stmts.len();
+            stmts.retain(|stmt| {
+                let Some(declared) = declared_locals(stmt) else {
+                    return true;
+                };
+                let own_references = count_references(std::slice::from_ref(stmt));
+                let removable = declared.iter().all(|local| {
+                    self.unexported_locals.contains(local)
+                        && references.get(local) == own_references.get(local)
+                }) && self.is_pure_stmt(stmt);
+                !removable
+            });
+            if stmts.len() == len {
+                return stmts;
+            }
+        }
+    }
+}
+
+/// The locals of a function, class or variable declaration without patterns.
+fn declared_locals(stmt: &Stmt) -> Option<Vec<Id>> {
+    match stmt {
+        Stmt::Decl(Decl::Fn(decl)) => Some(vec![decl.ident.to_id()]),
+        Stmt::Decl(Decl::Class(decl)) => Some(vec![decl.ident.to_id()]),
+        Stmt::Decl(Decl::Var(decl)) => decl
+            .decls
+            .iter()
+            .map(|decl| decl.name.as_ident().map(|ident| ident.to_id()))
+            .collect(),
+        _ => None,
+    }
+}
+
+fn count_references(stmts: &[Stmt]) -> HashMap<Id, usize> {
+    let mut counter = ReferenceCounter(HashMap::new());
+    stmts.visit_with(&mut counter);
+    counter.0
+}
+
+struct ReferenceCounter(HashMap<Id, usize>);
+impl Visit for ReferenceCounter {
+    noop_visit_type!();
+
+    fn visit_ident(&mut self, n: &Ident) {
+        *self.0.entry(n.to_id()).or_default() += 1;
+    }
+}
@@ -0,0 +1,309 @@
+use std::collections::{HashMap, HashSet};
+
+use swc_core::common::{Span, SyntaxContext, DUMMY_SP};
+use swc_core::ecma::ast::*;
+use swc_core::ecma::atoms::js_word;
+use swc_core::ecma::utils::{
+    find_pat_ids, private_ident, quote_ident, DestructuringFinder, ExprFactory,
+};
+use swc_core::ecma::visit::{noop_visit_type, FoldWith, Visit, VisitWith};
+
+use super::VirtualModuleRecordTransformer;
+use crate::utils::emit_warning;
+
+/// A binding that the code of a direct `eval` can see.
+pub struct VisibleBinding {
+    ident: Ident,
+    /// `const`, imports and the names of function and class expressions can not be assigned.
+    writable: bool,
+}
+
+/// The bindings visible at every direct `eval` call, indexed by the span of the call.
+pub type DirectEvalScopes = HashMap<Span, Vec<VisibleBinding>>;
+
+/// Collect the bindings visible at every direct `eval` call of `module`.
+///
+/// Scopes are complete when a call is resolved, so bindings declared after the call (hoisted or in
+/// their TDZ) are visible too, like in native code.
+pub fn collect_direct_eval_scopes(module: &Module, unresolved: SyntaxContext) -> DirectEvalScopes {
+    let mut collector = ScopeCollector {
+        unresolved,
+        frames: vec![],
+        stack: vec![],
+        function_frames: vec![],
+        calls: vec![],
+    };
+    module.visit_with(&mut collector);
+
+    let frames = collector.frames;
+    collector
+        .calls
+        .into_iter()
+        .map(|(span, stack)| {
+            let mut seen = HashSet::new();
+            let bindings = stack
+                .iter()
+                .rev()
+                .flat_map(|frame| &frames[*frame])
+                .filter(|binding| seen.insert(binding.ident.sym.clone()))
+                .map(|binding| VisibleBinding {
+                    ident: binding.ident.clone(),
+                    writable: binding.writable,
+                })
+                .collect();
+            (span, bindings)
+        })
+        .collect()
+}
+
+struct ScopeCollector {
+    unresolved: SyntaxContext,
+    /// The declarations of every scope, a scope is an index into this list.
+    frames: Vec<Vec<VisibleBinding>>,
+    /// The scope chain of the current node.
+    stack: Vec<usize>,
+    /// The scopes of the enclosing functions, where `var` is declared.
+    function_frames: Vec<usize>,
+    calls: Vec<(Span, Vec<usize>)>,
+}
+
+impl ScopeCollector {
+    fn scope<T: VisitWith<Self>>(&mut self, is_function: bool, declare: &[(Ident, bool)], n: &T) {
+        let frame = self.frames.len();
+        self.frames.push(vec![]);
+        self.stack.push(frame);
+        if is_function {
+            self.function_frames.push(frame);
+        }
+        for (ident, writable) in declare {
+            self.declare(frame, ident.clone(), *writable);
+        }
+        n.visit_children_with(self);
+        if is_function {
+            self.function_frames.pop();
+        }
+        self.stack.pop();
+    }
+    fn declare(&mut self, frame: usize, ident: Ident, writable: bool) {
+        self.frames[frame].push(VisibleBinding { ident, writable });
+    }
+    fn declare_pat<T>(&mut self, frame: usize, node: &T, writable: bool)
+    where
+        T: VisitWith<DestructuringFinder<Ident>>,
+    {
+        for ident in find_pat_ids::<_, Ident>(node) {
+            self.declare(frame, ident, writable);
+        }
+    }
+    fn current(&self) -> usize {
+        *self.stack.last().unwrap()
+    }
+}
+
+fn params<T>(node: &T) -> Vec<(Ident, bool)>
+where
+    T: VisitWith<DestructuringFinder<Ident>>,
+{
+    find_pat_ids::<_, Ident>(node)
+        .into_iter()
+        .map(|ident| (ident, true))
+        .collect()
+}
+
+impl Visit for ScopeCollector {
+    noop_visit_type!();
+    fn visit_module(&mut self, n: &Module) {
+        self.scope(true, &[], n);
+    }
+    fn visit_import_decl(&mut self, n: &ImportDecl) {
+        for specifier in &n.specifiers {
+            let local = match specifier {
+                ImportSpecifier::Named(spec) => &spec.local,
+                ImportSpecifier::Default(spec) => &spec.local,
+                ImportSpecifier::Namespace(spec) => &spec.local,
+            };
+            self.declare(self.current(), local.clone(), false);
+        }
+    }
+    fn visit_function(&mut self, n: &Function) {
+        self.scope(true, &params(&n.params), n);
+    }
+    fn visit_arrow_expr(&mut self, n: &ArrowExpr) {
+        self.scope(true, &params(&n.params), n);
+    }
+    fn visit_constructor(&mut self, n: &Constructor) {
+        self.scope(true, &params(&n.params), n);
+    }
+    fn visit_getter_prop(&mut self, n: &GetterProp) {
+        self.scope(true, &[], n);
+    }
+    fn visit_setter_prop(&mut self, n: &SetterProp) {
+        self.scope(true, &params(&n.param), n);
+    }
+    fn visit_static_block(&mut self, n: &StaticBlock) {
+        self.scope(true, &[], n);
+    }
+    fn visit_block_stmt(&mut self, n: &BlockStmt) {
+        self.scope(false, &[], n);
+    }
+    fn visit_for_stmt(&mut self, n: &ForStmt) {
+        self.scope(false, &[], n);
+    }
+    fn visit_for_in_stmt(&mut self, n: &ForInStmt) {
+        self.scope(false, &[], n);
+    }
+    fn visit_for_of_stmt(&mut self, n: &ForOfStmt) {
+        self.scope(false, &[], n);
+    }
+    fn visit_catch_clause(&mut self, n: &CatchClause) {
+        self.scope(false, &params(&n.param), n);
+    }
+    fn visit_var_decl(&mut self, n: &VarDecl) {
+        let frame = match n.kind {
+            VarDeclKind::Var => *self.function_frames.last().unwrap(),
+            _ => self.current(),
+        };
+        for decl in &n.decls {
+            self.declare_pat(frame, &decl.name, n.kind != VarDeclKind::Const);
+        }
+        n.visit_children_with(self);
+    }
+    fn visit_using_decl(&mut self, n: &UsingDecl) {
+        for decl in &n.decls {
+            self.declare_pat(self.current(), &decl.name, false);
+        }
+        n.visit_children_with(self);
+    }
+    fn visit_fn_decl(&mut self, n: &FnDecl) {
+        self.declare(self.current(), n.ident.clone(), true);
+        n.visit_children_with(self);
+    }
+    fn visit_class_decl(&mut self, n: &ClassDecl) {
+        self.declare(self.current(), n.ident.clone(), true);
+        n.visit_children_with(self);
+    }
+    fn visit_fn_expr(&mut self, n: &FnExpr) {
+        match &n.ident {
+            Some(ident) => self.scope(false, &[(ident.clone(), false)], n),
+            None => n.visit_children_with(self),
+        }
+    }
+    fn visit_class_expr(&mut self, n: &ClassExpr) {
+        match &n.ident {
+            Some(ident) => self.scope(false, &[(ident.clone(), false)], n),
+            None => n.visit_children_with(self),
+        }
+    }
+    fn visit_call_expr(&mut self, n: &CallExpr) {
+        if is_direct_eval(n, self.unresolved) {
+            self.calls.push((n.span, self.stack.clone()));
+        }
+        n.visit_children_with(self);
+    }
+}
+
+/// `eval(...)` where `eval` is not a local binding.
+fn is_direct_eval(call: &CallExpr, unresolved: SyntaxContext) -> bool {
+    match &call.callee {
+        Callee::Expr(callee) => match &**callee {
+            Expr::Ident(ident) => ident.sym == js_word!("eval") && ident.span.ctxt == unresolved,
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+impl VirtualModuleRecordTransformer {
+    pub fn is_direct_eval(&self, call: &CallExpr) -> bool {
+        is_direct_eval(call, self.unresolved)
+    }
+    /// eval(code)
+    /// =>
+    /// context.evaluate({ get x() { return x }, set x(value) { x = value } }, code)
+    ///
+    /// In functions, `arguments` is a data property of the scope, a getter would return its own
+    /// `arguments`. `this` and `new.target` are not names, the evaluated code can not see them.
+    ///
+    /// Without `config.directEval`, the call is compiled as an indirect eval with a warning.
+    pub fn fold_direct_eval(&mut self, call: CallExpr) -> Expr {
+        let Some(bindings) = self.direct_eval_scopes.remove(&call.span) else {
+            emit_warning(
+                call.span,
+                "Direct eval is compiled to an indirect eval, the evaluated code can not see the \
+                 bindings of the module. Enable `directEval` to pass them to `context.evaluate`.",
+            );
+            return call.fold_children_with(self).into();
+        };
+        self.uses_direct_eval = true;
+        if self.binds_this {
+            emit_warning(
+                call.span,
+                "The code of a direct eval in a function can not see `this` and `new.target` of \
+                 the function.",
+            );
+        }
+        let arguments = self.may_include_implicit_arguments.then(|| {
+            Prop::Shorthand(Ident::new(
+                js_word!("arguments"),
+                DUMMY_SP.with_ctxt(self.unresolved),
+            ))
+        });
+        let props = arguments
+            .into_iter()
+            .chain(
+                bindings
+                    .into_iter()
+                    .flat_map(|VisibleBinding { ident, writable }| {
+                        let key = PropName::Ident(quote_ident!(ident.sym.clone()));
+                        let getter = Prop::Getter(GetterProp {
+                            span: DUMMY_SP,
+                            key: key.clone(),
+                            type_ann: None,
+                            body: Some(BlockStmt {
+                                span: DUMMY_SP,
+                                stmts: vec![Stmt::Return(ReturnStmt {
+                                    span: DUMMY_SP,
+                                    arg: Some(Box::new(ident.clone().into())),
+                                })],
+                            }),
+                        });
+                        let setter = writable.then(|| {
+                            let value = private_ident!("value");
+                            Prop::Setter(SetterProp {
+                                span: DUMMY_SP,
+                                key,
+                                param: Box::new(value.clone().into()),
+                                body: Some(BlockStmt {
+                                    span: DUMMY_SP,
+                                    stmts: vec![Expr::from(value)
+                                        .make_assign_to(op!("="), ident.into())
+                                        .into_stmt()],
+                                }),
+                            })
+                        });
+                        [Some(getter), setter]
+                    })
+                    .flatten(),
+            )
+            .map(|prop| PropOrSpread::Prop(Box::new(prop)))
+            .collect();
+        let scope = ObjectLit {
+            span: DUMMY_SP,
+            props,
+        };
+        let mut args = vec![scope.as_arg()];
+        args.extend(call.args);
+        CallExpr {
+            span: call.span,
+            callee: self
+                .import_context_ident
+                .clone()
+                .make_member(quote_ident!("evaluate"))
+                .as_callee(),
+            args,
+            type_args: None,
+        }
+        .fold_with(self)
+        .into()
+    }
+}
@@ -0,0 +1,394 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use swc_core::common::{sync::Lrc, FileName, SourceMap, DUMMY_SP};
+use swc_core::ecma::ast::*;
+use swc_core::ecma::atoms::JsWord;
+use swc_core::ecma::parser::{parse_file_as_module, EsConfig, Syntax};
+use swc_core::ecma::utils::{drop_span, private_ident, ExprFactory};
+use swc_core::ecma::visit::{FoldWith, VisitMut, VisitMutWith};
+
+use super::VirtualModuleRecordTransformer;
+
+/// Same algorithm as `__addDisposableResource` and `__disposeResources` of tslib.
+///
+/// Intrinsics are read from `_` (the globalThis of the compartment) like every other global.
+const HELPERS: &str = r#"
+function addDisposableResource(env, value, async) {
+    if (value !== null && value !== void 0) {
+        if (typeof value !== "object" && typeof value !== "function") throw new _.TypeError("Object expected.");
+        var dispose, inner;
+        if (async) {
+            if (!_.Symbol.asyncDispose) throw new _.TypeError("Symbol.asyncDispose is not defined.");
+            dispose = value[_.Symbol.asyncDispose];
+        }
+        if (dispose === void 0) {
+            if (!_.Symbol.dispose) throw new _.TypeError("Symbol.dispose is not defined.");
+            dispose = value[_.Symbol.dispose];
+            if (async) inner = dispose;
+        }
+        if (typeof dispose !== "function") throw new _.TypeError("Object not disposable.");
+        if (inner) dispose = function () {
+            try {
+                inner.call(this);
+            } catch (e) {
+                return _.Promise.reject(e);
+            }
+        };
+        env.stack.push({ value: value, dispose: dispose, async: async });
+    } else if (async) {
+        env.stack.push({ async: true });
+    }
+    return value;
+}
+function disposeResources(env) {
+    function fail(e) {
+        env.error = env.hasError ? suppress(e, env.error) : e;
+        env.hasError = true;
+    }
+    function suppress(error, suppressed) {
+        var message = "An error was suppressed during disposal.";
+        if (typeof _.SuppressedError === "function") return new _.SuppressedError(error, suppressed, message);
+        var e = new _.Error(message);
+        e.name = "SuppressedError";
+        e.error = error;
+        e.suppressed = suppressed;
+        return e;
+    }
+    var r, s = 0;
+    function next() {
+        while (r = env.stack.pop()) {
+            try {
+                if (!r.async && s === 1) return s = 0, env.stack.push(r), _.Promise.resolve().then(next);
+                if (r.dispose) {
+                    var result = r.dispose.call(r.value);
+                    if (r.async) return s |= 2, _.Promise.resolve(result).then(next, function (e) {
+                        fail(e);
+                        return next();
+                    });
+                } else s |= 1;
+            } catch (e) {
+                fail(e);
+            }
+        }
+        if (s === 1) return env.hasError ? _.Promise.reject(env.error) : _.Promise.resolve();
+        if (env.hasError) throw env.error;
+    }
+    return next();
+}
+"#;
+
+const ENV: &str = r#"
+var env = { stack: [], error: void 0, hasError: false };
+"#;
+
+const SYNC_DISPOSAL: &str = r#"
+try {} catch (e) {
+    env.error = e;
+    env.hasError = true;
+} finally {
+    disposeResources(env);
+}
+"#;
+
+const ASYNC_DISPOSAL: &str = r#"
+try {} catch (e) {
+    env.error = e;
+    env.hasError = true;
+} finally {
+    var result = disposeResources(env);
+    if (result) await result;
+}
+"#;
+
+/// The templates above, parsed once. Their free identifiers are bound by `Disposal::bind`.
+struct Templates {
+    helpers: Vec<Stmt>,
+    env: Vec<Stmt>,
+    sync_disposal: Vec<Stmt>,
+    async_disposal: Vec<Stmt>,
+}
+
+static TEMPLATES: OnceLock<Templates> = OnceLock::new();
+
+impl Templates {
+    fn get() -> &'static Templates {
+        TEMPLATES.get_or_init(|| Templates {
+            helpers: parse(HELPERS),
+            env: parse(ENV),
+            sync_disposal: parse(SYNC_DISPOSAL),
+            async_disposal: parse(ASYNC_DISPOSAL),
+        })
+    }
+}
+
+fn parse(code: &str) -> Vec<Stmt> {
+    let cm: Lrc<SourceMap> = Default::default();
+    let fm = cm.new_source_file(FileName::Anon, code.into());
+    let module = parse_file_as_module(
+        &fm,
+        Syntax::Es(EsConfig::default()),
+        EsVersion::latest(),
+        None,
+        &mut vec![],
+    )
+    .expect("the disposal helpers should parse");
+    drop_span(module)
+        .body
+        .into_iter()
+        .filter_map(|item| item.stmt())
+        .collect()
+}
+
+/// The resources declared by `using` in a module.
+///
+/// They are disposed when the evaluation of their block completes, in the reverse order of their
+/// declaration, whether the evaluation throws or not. The block of a top-level `using` is the
+/// module body.
+pub struct Disposal {
+    env: Ident,
+    add_disposable_resource: Ident,
+    dispose_resources: Ident,
+    result: Ident,
+    /// A `using` is declared at the top level of the module.
+    top_level: bool,
+}
+
+impl Disposal {
+    fn new() -> Self {
+        Self {
+            env: private_ident!("env"),
+            add_disposable_resource: private_ident!("addDisposableResource"),
+            dispose_resources: private_ident!("disposeResources"),
+            result: private_ident!("result"),
+            top_level: false,
+        }
+    }
+    /// `var env = { stack: [], ... }` with the given kind.
+    fn declare_env(&self, env: &Ident, kind: VarDeclKind, global_this: &Ident) -> Stmt {
+        let mut stmts = self.bind(&Templates::get().env, env, global_this);
+        if let Some(Stmt::Decl(Decl::Var(decl))) = stmts.first_mut() {
+            decl.kind = kind;
+        }
+        stmts.remove(0)
+    }
+    /// Run `stmts` inside `try` and dispose the resources of `env` in `finally`.
+    fn wrap(&self, stmts: Vec<Stmt>, env: &Ident, is_async: bool, global_this: &Ident) -> Stmt {
+        let templates = Templates::get();
+        let mut disposal = self.bind(
+            if is_async {
+                &templates.async_disposal
+            } else {
+                &templates.sync_disposal
+            },
+            env,
+            global_this,
+        );
+        if let Some(Stmt::Try(try_stmt)) = disposal.first_mut() {
+            try_stmt.block.stmts = stmts;
+        }
+        disposal.remove(0)
+    }
+    fn bind(&self, stmts: &[Stmt], env: &Ident, global_this: &Ident) -> Vec<Stmt> {
+        let mut stmts = stmts.to_vec();
+        stmts.visit_mut_with(&mut ReplaceIdent(HashMap::from([
+            ("_".into(), global_this.clone()),
+            ("env".into(), env.clone()),
+            (
+                "addDisposableResource".into(),
+                self.add_disposable_resource.clone(),
+            ),
+            ("disposeResources".into(), self.dispose_resources.clone()),
+            ("result".into(), self.result.clone()),
+        ])));
+        stmts
+    }
+}
+
+/// Bind the free identifiers of the helpers to the private identifiers of the transformer.
+struct ReplaceIdent(HashMap<JsWord, Ident>);
+impl VisitMut for ReplaceIdent {
+    fn visit_mut_ident(&mut self, n: &mut Ident) {
+        if let Some(ident) = self.0.get(&n.sym) {
+            *n = ident.clone();
+        }
+    }
+    fn visit_mut_member_prop(&mut self, n: &mut MemberProp) {
+        if let MemberProp::Computed(n) = n {
+            n.visit_mut_with(self);
+        }
+    }
+    fn visit_mut_prop_name(&mut self, n: &mut PropName) {
+        if let PropName::Computed(n) = n {
+            n.visit_mut_with(self);
+        }
+    }
+}
+
+fn is_using(stmt: &Stmt) -> bool {
+    matches!(stmt, Stmt::Decl(Decl::Using(_)))
+}
+
+impl VirtualModuleRecordTransformer {
+    /// using x = expr
+    /// =>
+    /// const x = addDisposableResource(env, expr, false)
+    fn using_to_const(&mut self, using: UsingDecl, env: Option<&Ident>) -> VarDecl {
+        let disposal = self.disposal.get_or_insert_with(Disposal::new);
+        let (env, add) = (
+            env.unwrap_or(&disposal.env).clone(),
+            disposal.add_disposable_resource.clone(),
+        );
+        let decls = using
+            .decls
+            .into_iter()
+            .map(|decl| VarDeclarator {
+                init: decl.init.map(|init| {
+                    Box::new(add.clone().as_call(
+                        DUMMY_SP,
+                        vec![
+                            env.clone().as_arg(),
+                            init.as_arg(),
+                            Expr::from(using.is_await).as_arg(),
+                        ],
+                    ))
+                }),
+                ..decl
+            })
+            .collect();
+        VarDecl {
+            span: using.span,
+            kind: VarDeclKind::Const,
+            declare: false,
+            decls,
+        }
+    }
+    pub fn fold_top_level_using(&mut self, using: UsingDecl) -> Vec<Stmt> {
+        let decl = self.using_to_const(using, None);
+        if let Some(disposal) = &mut self.disposal {
+            disposal.top_level = true;
+        }
+        self.fold_declaration_to_multiple(decl.into())
+    }
+    /// A block (or a function body) with `using` declarations disposes them when it completes.
+    ///
+    /// { using x = expr; ... }
+    /// =>
+    /// {
+    ///     const env = { stack: [], error: void 0, hasError: false };
+    ///     try { const x = addDisposableResource(env, expr, false); ... }
+    ///     catch (e) { env.error = e; env.hasError = true; }
+    ///     finally { disposeResources(env); }
+    /// }
+    pub fn fold_block_with_using(&mut self, block: BlockStmt) -> BlockStmt {
+        if !block.stmts.iter().any(is_using) {
+            return block.fold_children_with(self);
+        }
+        let env = private_ident!("env");
+        let is_async = block
+            .stmts
+            .iter()
+            .any(|stmt| matches!(stmt, Stmt::Decl(Decl::Using(using)) if using.is_await));
+        let stmts = block
+            .stmts
+            .into_iter()
+            .map(|stmt| match stmt {
+                Stmt::Decl(Decl::Using(using)) => self.using_to_const(*using, Some(&env)).into(),
+                stmt => stmt,
+            })
+            .collect::<Vec<Stmt>>()
+            .fold_with(self);
+        let Some(disposal) = &self.disposal else {
+            unreachable!("using_to_const creates the disposal")
+        };
+        let global_this = &self.global_this_ident;
+        BlockStmt {
+            span: block.span,
+            stmts: vec![
+                disposal.declare_env(&env, VarDeclKind::Const, global_this),
+                disposal.wrap(stmts, &env, is_async, global_this),
+            ],
+        }
+    }
+    /// Define the disposal helpers, and dispose the resources of top-level `using` declarations
+    /// at the end of the evaluation.
+    ///
+    /// Function declarations are initialized before the module body is evaluated, they and their
+    /// live export tracing come first in the `try` block, so a throw in the body does not leave
+    /// their exports uninitialized.
+    pub fn dispose_at_end(&mut self, stmts: Vec<Stmt>) -> Vec<Stmt> {
+        let Some(disposal) = self.disposal.take() else {
+            return stmts;
+        };
+        self.uses_global_lookup = true;
+        let global_this = &self.global_this_ident;
+        let mut result = disposal.bind(&Templates::get().helpers, &disposal.env, global_this);
+        if !disposal.top_level {
+            result.extend(stmts);
+            return result;
+        }
+        let (mut hoisted, mut rest) = (vec![], vec![]);
+        let mut function = None;
+        for stmt in stmts {
+            if let Stmt::Decl(Decl::Fn(decl)) = &stmt {
+                function = Some(decl.ident.to_id());
+                hoisted.push(stmt);
+            } else if self.is_hoisted_tracing(&stmt, function.as_ref()) {
+                hoisted.push(stmt);
+            } else {
+                function = None;
+                rest.push(stmt);
+            }
+        }
+        hoisted.append(&mut rest);
+        result.push(disposal.declare_env(&disposal.env, VarDeclKind::Var, global_this));
+        result.push(disposal.wrap(
+            hoisted,
+            &disposal.env,
+            self.uses_top_level_await,
+            global_this,
+        ));
+        result
+    }
+    /// `__.x = f` after the declaration of `f`, or `__.default = { default: function () {} }.default`.
+    fn is_hoisted_tracing(&self, stmt: &Stmt, function: Option<&Id>) -> bool {
+        let Stmt::Expr(ExprStmt { expr, .. }) = stmt else {
+            return false;
+        };
+        let mut expr = &**expr;
+        let mut assigned = false;
+        while let Expr::Assign(AssignExpr {
+            op: op!("="),
+            left: PatOrExpr::Expr(left),
+            right,
+            ..
+        }) = expr
+        {
+            match &**left {
+                Expr::Member(MemberExpr { obj, .. })
+                    if obj.as_ident().map(Ident::to_id)
+                        == Some(self.module_env_record_ident.to_id()) => {}
+                _ => return false,
+            }
+            assigned = true;
+            expr = right;
+        }
+        if !assigned {
+            return false;
+        }
+        match expr {
+            Expr::Ident(ident) => function == Some(&ident.to_id()),
+            Expr::Member(MemberExpr { obj, .. }) => match &**obj {
+                Expr::Object(ObjectLit { props, .. }) => matches!(
+                    props.as_slice(),
+                    [PropOrSpread::Prop(prop)] if matches!(
+                        &**prop,
+                        Prop::KeyValue(KeyValueProp { value, .. }) if value.is_fn_expr()
+                    )
+                ),
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,411 @@
+use std::collections::HashSet;
+
+use swc_core::common::DUMMY_SP;
+use swc_core::ecma::ast::*;
+use swc_core::ecma::atoms::JsWord;
+use swc_core::ecma::utils::{find_pat_ids, private_ident, quote_ident, ExprFactory};
+
+use super::binding_descriptor::{module_export_name_to_str, Binding, ExportBinding, ModuleBinding};
+use super::VirtualModuleRecordTransformer;
+use crate::utils::{emit_error, key_value};
+
+impl VirtualModuleRecordTransformer {
+    /// The static module record of SES compartments, `execute` is called by the module program:
+    ///
+    /// ```js
+    /// {
+    ///     imports: ["mod"],
+    ///     exports: ["x"],
+    ///     reexports: [],
+    ///     __syncModuleProgram__: "" + (({ imports, liveVar, onceVar, importMeta }) => {
+    ///         let a;
+    ///         imports([["mod", [["default", [(value) => a = value]]]]], []);
+    ///         (function (__, context) { ... })({
+    ///             get a() { return a },
+    ///             set x(value) { liveVar.x(value) },
+    ///         }, { globalThis: globalThis, importMeta: importMeta });
+    ///     }),
+    ///     __liveExportMap__: { x: ["x", false] },
+    ///     __fixedExportMap__: {},
+    ///     __reexportMap__: {},
+    ///     __needsImportMeta__: false,
+    /// }
+    /// ```
+    ///
+    /// The program is the source text of the functor, SES evaluates it inside the compartment so
+    /// `globalThis` is the global object of the compartment. `liveVar` and `onceVar` are keyed by
+    /// the export name. Exports of top-level `const` and anonymous default exports are fixed.
+    pub fn endo_module_record(&self, stmts: Vec<Stmt>) -> Expr {
+        if self.uses_top_level_await {
+            emit_error(
+                DUMMY_SP,
+                "The endo protocol does not support top-level await.",
+            );
+        }
+        // Endo reads precompiled records by their `__` properties. `$HASH` of a custom template
+        // is the same hash.
+        if self.config.hash {
+            emit_error(
+                DUMMY_SP,
+                "The endo protocol does not support `config.hash`.",
+            );
+        }
+        if self.uses_dynamic_import || self.uses_direct_eval {
+            emit_error(
+                DUMMY_SP,
+                "The endo protocol does not support dynamic import and direct eval.",
+            );
+        }
+        let fixed = self.fixed_exports(&stmts);
+
+        let imports_ident = private_ident!("imports");
+        let live_var_ident = private_ident!("liveVar");
+        let once_var_ident = private_ident!("onceVar");
+        let import_meta_ident = private_ident!("importMeta");
+
+        let mut specifiers: Vec<JsWord> = vec![];
+        let mut specifier = |from: &Str| {
+            if !specifiers.contains(&from.value) {
+                specifiers.push(from.value.clone());
+            }
+        };
+        let mut exports = vec![];
+        let mut reexports = vec![];
+        let mut live_export_map = vec![];
+        let mut fixed_export_map = vec![];
+        let mut reexport_map: Vec<(JsWord, Vec<Expr>)> = vec![];
+        let mut updaters: Vec<(JsWord, Vec<Expr>)> = vec![];
+        let mut locals = vec![];
+        let mut accessors: Vec<Accessor> = vec![];
+
+        for binding in &self.bindings {
+            match binding {
+                Binding::Import(import) => {
+                    specifier(&import.from);
+                    let alias = import.alias.as_ref().unwrap();
+                    let local = private_ident!(alias.sym.clone());
+                    let value = private_ident!("value");
+                    let updater = ArrowExpr {
+                        span: DUMMY_SP,
+                        params: vec![value.clone().into()],
+                        body: Box::new(BlockStmtOrExpr::Expr(Box::new(
+                            Expr::from(value).make_assign_to(op!("="), local.clone().into()),
+                        ))),
+                        is_async: false,
+                        is_generator: false,
+                        type_params: None,
+                        return_type: None,
+                    };
+                    let update = array(vec![
+                        import.import.clone().into(),
+                        array(vec![updater.into()]),
+                    ]);
+                    push_to(&mut updaters, &import.from.value, update);
+                    accessor(&mut accessors, alias.sym.to_string()).1 = Some(local.clone());
+                    locals.push(local);
+                }
+                Binding::Export(export) => match (&export.from, &export.export) {
+                    (None, ModuleBinding::ModuleExportName(name)) => {
+                        let name = module_export_name_to_str(export.alias.as_ref().unwrap_or(name));
+                        let var = if fixed.contains(&name) {
+                            let entry = array(vec![name.as_str().into()]);
+                            fixed_export_map.push(str_key_value(&name, entry));
+                            &once_var_ident
+                        } else {
+                            let entry = array(vec![name.as_str().into(), false.into()]);
+                            live_export_map.push(str_key_value(&name, entry));
+                            &live_var_ident
+                        };
+                        let value = private_ident!("value");
+                        let update = Expr::from(MemberExpr {
+                            span: DUMMY_SP,
+                            obj: Box::new(var.clone().into()),
+                            prop: member_prop(&name),
+                        })
+                        .as_call(DUMMY_SP, vec![value.clone().as_arg()]);
+                        accessor(&mut accessors, name.clone()).2 = Some(Prop::Setter(SetterProp {
+                            span: DUMMY_SP,
+                            key: prop_name(&name),
+                            param: Box::new(value.into()),
+                            body: Some(BlockStmt {
+                                span: DUMMY_SP,
+                                stmts: vec![update.into_stmt()],
+                            }),
+                        }));
+                        exports.push(name);
+                    }
+                    (None, _) => unreachable!("only re-exports export namespaces"),
+                    (Some(from), ModuleBinding::Namespace) if export.alias.is_none() => {
+                        specifier(from);
+                        reexports.push(from.value.clone());
+                    }
+                    (Some(from), import) => {
+                        specifier(from);
+                        let import: Expr = import.clone().into();
+                        let name = match (&export.alias, &import) {
+                            (Some(alias), _) => module_export_name_to_str(alias),
+                            (None, Expr::Lit(Lit::Str(name))) => name.value.to_string(),
+                            _ => unreachable!("namespaces are re-exported with a name"),
+                        };
+                        push_to(
+                            &mut reexport_map,
+                            &from.value,
+                            array(vec![import, name.as_str().into()]),
+                        );
+                        exports.push(name);
+                    }
+                },
+            }
+        }
+
+        let update_record = array(
+            specifiers
+                .iter()
+                .map(|from| {
+                    let updaters = updaters
+                        .iter()
+                        .find(|(key, _)| key == from)
+                        .map(|(_, updaters)| updaters.clone())
+                        .unwrap_or_default();
+                    array(vec![from.clone().into(), array(updaters)])
+                })
+                .collect(),
+        );
+        let reexports_expr = array(reexports.iter().map(|from| from.clone().into()).collect());
+        let mut functor_body = vec![];
+        if !locals.is_empty() {
+            functor_body.push(
+                VarDecl {
+                    span: DUMMY_SP,
+                    kind: VarDeclKind::Let,
+                    declare: false,
+                    decls: locals
+                        .into_iter()
+                        .map(|local| VarDeclarator {
+                            span: DUMMY_SP,
+                            name: local.into(),
+                            init: None,
+                            definite: false,
+                        })
+                        .collect(),
+                }
+                .into(),
+            );
+        }
+        functor_body.push(
+            imports_ident
+                .clone()
+                .as_call(
+                    DUMMY_SP,
+                    vec![update_record.as_arg(), reexports_expr.clone().as_arg()],
+                )
+                .into_stmt(),
+        );
+        let env = ObjectLit {
+            span: DUMMY_SP,
+            props: accessors
+                .into_iter()
+                .flat_map(|(name, getter, setter)| {
+                    let getter = getter.map(|local| {
+                        Prop::from(GetterProp {
+                            span: DUMMY_SP,
+                            key: prop_name(&name),
+                            type_ann: None,
+                            body: Some(BlockStmt {
+                                span: DUMMY_SP,
+                                stmts: vec![Stmt::Return(ReturnStmt {
+                                    span: DUMMY_SP,
+                                    arg: Some(Box::new(local.into())),
+                                })],
+                            }),
+                        })
+                    });
+                    [getter, setter]
+                })
+                .flatten()
+                .map(|prop| PropOrSpread::Prop(Box::new(prop)))
+                .collect(),
+        };
+        let context = ObjectLit {
+            span: DUMMY_SP,
+            props: vec![
+                key_value("globalThis".into(), quote_ident!("globalThis").into()),
+                key_value("importMeta".into(), import_meta_ident.clone().into()),
+            ],
+        };
+        functor_body.push(
+            FnExpr {
+                ident: None,
+                function: Box::new(self.execute(stmts)),
+            }
+            .wrap_with_paren()
+            .as_call(DUMMY_SP, vec![env.as_arg(), context.as_arg()])
+            .into_stmt(),
+        );
+        let functor = ArrowExpr {
+            span: DUMMY_SP,
+            params: vec![ObjectPat {
+                span: DUMMY_SP,
+                props: [
+                    ("imports", imports_ident),
+                    ("liveVar", live_var_ident),
+                    ("onceVar", once_var_ident),
+                    ("importMeta", import_meta_ident),
+                ]
+                .into_iter()
+                .map(|(key, value)| {
+                    ObjectPatProp::KeyValue(KeyValuePatProp {
+                        key: PropName::Ident(quote_ident!(key)),
+                        value: Box::new(value.into()),
+                    })
+                })
+                .collect(),
+                optional: false,
+                type_ann: None,
+            }
+            .into()],
+            body: Box::new(BlockStmtOrExpr::BlockStmt(BlockStmt {
+                span: DUMMY_SP,
+                stmts: functor_body,
+            })),
+            is_async: false,
+            is_generator: false,
+            type_params: None,
+            return_type: None,
+        };
+        // SES evaluates the source text of the functor.
+        let program = BinExpr {
+            span: DUMMY_SP,
+            op: op!(bin, "+"),
+            left: "".into(),
+            right: Box::new(functor.wrap_with_paren()),
+        };
+
+        let object = |entries: Vec<PropOrSpread>| -> Expr {
+            ObjectLit {
+                span: DUMMY_SP,
+                props: entries,
+            }
+            .into()
+        };
+        ObjectLit {
+            span: DUMMY_SP,
+            props: vec![
+                key_value(
+                    "imports".into(),
+                    array(specifiers.into_iter().map(Expr::from).collect()),
+                ),
+                key_value(
+                    "exports".into(),
+                    array(exports.iter().map(|name| name.as_str().into()).collect()),
+                ),
+                key_value("reexports".into(), reexports_expr),
+                key_value("__syncModuleProgram__".into(), program.into()),
+                key_value("__liveExportMap__".into(), object(live_export_map)),
+                key_value("__fixedExportMap__".into(), object(fixed_export_map)),
+                key_value(
+                    "__reexportMap__".into(),
+                    object(
+                        reexport_map
+                            .into_iter()
+                            .map(|(from, entries)| str_key_value(&from, array(entries)))
+                            .collect(),
+                    ),
+                ),
+                key_value("__needsImportMeta__".into(), self.uses_import_meta.into()),
+            ],
+        }
+        .into()
+    }
+    /// The export names of top-level `const` declarations and of anonymous default exports, they
+    /// are assigned exactly once.
+    fn fixed_exports(&self, stmts: &[Stmt]) -> HashSet<String> {
+        let mut fixed: HashSet<String> = stmts
+            .iter()
+            .filter_map(|stmt| match stmt {
+                Stmt::Decl(Decl::Var(var)) if var.kind == VarDeclKind::Const => Some(var),
+                _ => None,
+            })
+            .flat_map(|var| find_pat_ids::<_, Id>(&var.decls))
+            .filter_map(|id| self.local_resolved_bindings.get(&id))
+            .flatten()
+            .map(module_export_name_to_str)
+            .collect();
+        // `export default function () {}` is not traced, the binding is assigned once.
+        let traced = self
+            .local_resolved_bindings
+            .values()
+            .flatten()
+            .any(|name| module_export_name_to_str(name) == "default");
+        let exports_default = self.bindings.iter().any(|binding| match binding {
+            Binding::Export(ExportBinding {
+                export: ModuleBinding::ModuleExportName(name),
+                alias,
+                from: None,
+            }) => module_export_name_to_str(alias.as_ref().unwrap_or(name)) == "default",
+            _ => false,
+        });
+        if exports_default && !traced {
+            fixed.insert("default".to_string());
+        }
+        fixed
+    }
+}
+
+/// A property of the `__` passed to `execute`: the local of an import and the setter of an export.
+type Accessor = (String, Option<Ident>, Option<Prop>);
+
+fn accessor(accessors: &mut Vec<Accessor>, name: String) -> &mut Accessor {
+    let index = match accessors.iter().position(|(key, ..)| *key == name) {
+        Some(index) => index,
+        None => {
+            accessors.push((name, None, None));
+            accessors.len() - 1
+        }
+    };
+    &mut accessors[index]
+}
+
+fn array(elems: Vec<Expr>) -> Expr {
+    ArrayLit {
+        span: DUMMY_SP,
+        elems: elems.into_iter().map(|elem| Some(elem.as_arg())).collect(),
+    }
+    .into()
+}
+
+fn push_to(groups: &mut Vec<(JsWord, Vec<Expr>)>, key: &JsWord, value: Expr) {
+    match groups.iter_mut().find(|(group, _)| group == key) {
+        Some((_, values)) => values.push(value),
+        None => groups.push((key.clone(), vec![value])),
+    }
+}
+
+fn prop_name(name: &str) -> PropName {
+    if Ident::verify_symbol(name).is_ok() {
+        PropName::Ident(quote_ident!(name))
+    } else {
+        PropName::Str(name.into())
+    }
+}
+
+fn member_prop(name: &str) -> MemberProp {
+    if Ident::verify_symbol(name).is_ok() {
+        MemberProp::Ident(quote_ident!(name))
+    } else {
+        MemberProp::Computed(ComputedPropName {
+            span: DUMMY_SP,
+            expr: Box::new(name.into()),
+        })
+    }
+}
+
+fn str_key_value(key: &str, value: Expr) -> PropOrSpread {
+    PropOrSpread::Prop(Box::new(
+        KeyValueProp {
+            key: prop_name(key),
+            value: Box::new(value),
+        }
+        .into(),
+    ))
+}
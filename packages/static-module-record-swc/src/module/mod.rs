@@ -1,18 +1,32 @@
 /// Describes the import/export bindings of a JS module.
-mod binding_descriptor;
+pub(crate) mod binding_descriptor;
 /// Code generation for VirtualModuleRecord.
 mod codegen;
 pub mod config;
+/// Remove the exports that no module imports.
+mod dead_exports;
+/// Pass the lexical scope of direct `eval` calls to the host.
+mod direct_eval;
+/// Dispose the resources of top-level `using` declarations.
+mod disposal;
+/// Code generation for the static module records of SES compartments.
+mod endo;
 /// Scan the binding_descriptor inside a JS module.
 mod scanner;
+/// Find out whether `execute` has side effects.
+mod side_effects;
+/// The user-defined template of `Template::Custom`.
+mod template;
 /// Transform bindings into VirtualModuleRecord.
 mod transformer;
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 use self::{binding_descriptor::*, config::Config};
+use crate::manifest::Manifest;
+use sha2::{Digest, Sha256};
 
-use swc_core::common::{Mark, SyntaxContext};
+use swc_core::common::{comments::Comments, Mark, SyntaxContext};
 use swc_core::ecma::ast::*;
 use swc_core::ecma::utils::private_ident;
 
@@ -22,10 +36,14 @@ pub struct VirtualModuleRecordTransformer {
     uses_top_level_await: bool,
     uses_dynamic_import: bool,
     uses_global_lookup: bool,
+    uses_direct_eval: bool,
+    side_effect_free: bool,
+    /// The string literals passed to `import()`.
+    dynamic_imports: Vec<String>,
 
     bindings: Vec<Binding>,
-    imported_ident: HashMap<Id, (ModuleBinding, Str)>,
-    local_resolved_bindings: Vec<LiveExportTracingBinding>,
+    imported_ident: HashMap<Id, usize>,
+    local_resolved_bindings: LiveExportTracingBindings,
     unresolved: SyntaxContext,
 
     module_env_record_ident: Ident,
@@ -33,6 +51,25 @@ pub struct VirtualModuleRecordTransformer {
     global_this_ident: Ident,
 
     may_include_implicit_arguments: bool,
+    /// Inside a non-arrow function or a class, where `this` is not the `undefined` of the module.
+    binds_this: bool,
+    disposal: Option<disposal::Disposal>,
+    direct_eval_scopes: direct_eval::DirectEvalScopes,
+    /// The comments of the module, for `/*#__PURE__*/` annotations.
+    comments: Option<Box<dyn Comments>>,
+    /// The SHA-256 of the source text.
+    source_hash: Option<Vec<u8>>,
+    /// The hash of the source text and of the config, `hash` of the record and `$HASH` of
+    /// custom templates.
+    hash: Option<String>,
+    /// The record without `execute`, with the split template.
+    record_metadata: Option<serde_json::Value>,
+    /// The export names removed by `config.used_exports`.
+    unused_exports: HashSet<String>,
+    /// The local bindings that are no longer exported because of `unused_exports`.
+    unexported_locals: HashSet<Id>,
+    /// The names of `export * from` by the specifier, known when the graph is compiled together.
+    star_exports: HashMap<String, Vec<String>>,
 
     pub config: Config,
     pub file_name: Option<String>,
@@ -45,9 +82,22 @@ impl VirtualModuleRecordTransformer {
             uses_top_level_await: false,
             uses_dynamic_import: false,
             uses_global_lookup: false,
+            uses_direct_eval: false,
+            side_effect_free: false,
+            dynamic_imports: Vec::new(),
             may_include_implicit_arguments: false,
+            binds_this: false,
+            disposal: None,
+            direct_eval_scopes: HashMap::new(),
+            comments: None,
+            source_hash: None,
+            hash: None,
+            record_metadata: None,
+            star_exports: HashMap::new(),
+            unused_exports: HashSet::new(),
+            unexported_locals: HashSet::new(),
             bindings: Vec::new(),
-            local_resolved_bindings: Vec::new(),
+            local_resolved_bindings: HashMap::new(),
             module_env_record_ident: private_ident!("__"),
             import_context_ident: private_ident!("context"),
             global_this_ident: private_ident!("_"),
@@ -57,4 +107,74 @@ impl VirtualModuleRecordTransformer {
             imported_ident: HashMap::new(),
         }
     }
+    /// Read `/*#__PURE__*/` annotations from `comments`, otherwise every call is a side effect.
+    pub fn with_comments(mut self, comments: impl Comments + 'static) -> Self {
+        self.comments = Some(Box::new(comments));
+        self
+    }
+    /// Hash `source`, the input file, for `config.hash` and `$HASH` of custom templates.
+    ///
+    /// For WebAssembly and assets this is the binary, not the generated module, so the hash
+    /// changes only when the file does.
+    pub fn with_source(mut self, source: impl AsRef<[u8]>) -> Self {
+        self.source_hash = Some(Sha256::digest(source).to_vec());
+        self
+    }
+    /// Re-export these names instead of `export * from` their specifier. Ambiguous names must be
+    /// excluded already.
+    pub(crate) fn with_star_exports(mut self, star_exports: HashMap<String, Vec<String>>) -> Self {
+        self.star_exports = star_exports;
+        self
+    }
+    /// The SHA-256 of the source text, of the version of this crate and of everything in the
+    /// config that changes the record, so the same record has the same hash under any template.
+    fn record_hash(&self) -> Option<String> {
+        let source_hash = self.source_hash.as_ref()?;
+        let mut config = serde_json::to_value(&self.config).unwrap();
+        let fields = config.as_object_mut().unwrap();
+        fields.remove("template");
+        // The exports used in other modules do not change this record.
+        let used_exports = self
+            .file_name
+            .as_ref()
+            .and_then(|file_name| self.config.used_exports.get(file_name));
+        fields.insert("usedExports".into(), serde_json::json!(used_exports));
+        let star_exports: BTreeMap<_, _> = self.star_exports.iter().collect();
+        fields.insert("starExports".into(), serde_json::json!(star_exports));
+        let mut hasher = Sha256::new();
+        hasher.update(source_hash);
+        // Another version of the transformer may emit another record for the same input.
+        hasher.update(env!("CARGO_PKG_VERSION"));
+        // The keys of serde_json objects are sorted.
+        hasher.update(config.to_string());
+        Some(
+            hasher
+                .finalize()
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect(),
+        )
+    }
+    /// The import and export bindings of the last transformed module.
+    pub(crate) fn bindings(&self) -> &[Binding] {
+        &self.bindings
+    }
+    /// The manifest of the last transformed module.
+    pub fn manifest(&self) -> Manifest {
+        let mut imports: Vec<String> = vec![];
+        let from = self.bindings.iter().filter_map(|binding| match binding {
+            Binding::Import(import) => Some(import.from.value.to_string()),
+            Binding::Export(export) => export.from.as_ref().map(|from| from.value.to_string()),
+        });
+        for specifier in from.chain(self.dynamic_imports.iter().cloned()) {
+            if !imports.contains(&specifier) {
+                imports.push(specifier);
+            }
+        }
+        Manifest {
+            side_effects: !self.side_effect_free,
+            imports,
+            record: self.record_metadata.clone(),
+        }
+    }
 }
@@ -1,126 +1,103 @@
 use std::collections::HashMap;
+use std::mem::take;
 
 use super::{binding_descriptor::*, VirtualModuleRecordTransformer};
 use swc_core::common::DUMMY_SP;
 use swc_core::ecma::ast::*;
 use swc_core::ecma::utils::{contains_top_level_await, private_ident};
-use swc_core::ecma::visit::{Visit, VisitWith};
+use swc_core::ecma::visit::{noop_visit_type, Visit, VisitWith};
 
-struct ScannerFirstPass(HashMap<Id, (ModuleBinding, Str)>);
-impl Visit for ScannerFirstPass {
-    fn visit_import_decl(&mut self, n: &ImportDecl) {
-        if n.type_only {
+/// Collects the bindings of a module in one pass over its top-level items.
+struct Scanner {
+    bindings: Vec<Binding>,
+    phantom_import_binding_id: u32,
+    /// Imported local bindings and the index of their ImportBinding in `bindings`.
+    imported_ident: HashMap<Id, usize>,
+    /// `export { x }` that appears before `import { x }`, fixed up by `finish`.
+    pending_exports: Vec<(usize, Id, ModuleExportName)>,
+    live_export_tracing_bindings: LiveExportTracingBindings,
+    /// The names to re-export instead of `export * from` their specifier.
+    star_exports: HashMap<String, Vec<String>>,
+}
+impl Scanner {
+    /// Scan the bindings of an import declaration, it is consumed because imports are dropped.
+    fn scan_import(&mut self, import: ImportDecl) {
+        let from = *import.src;
+        if import.specifiers.is_empty() {
+            self.phantom_import(from);
             return;
         }
-        for specifier in n.specifiers.iter() {
-            match specifier {
-                ImportSpecifier::Named(named) => {
-                    self.0.insert(
-                        named.local.to_id(),
-                        (
-                            named
-                                .imported
-                                .clone()
-                                .unwrap_or_else(|| named.local.clone().into())
-                                .into(),
-                            *n.src.clone(),
-                        ),
-                    );
-                }
-                ImportSpecifier::Default(default) => {
-                    self.0.insert(
-                        default.local.to_id(),
-                        (ModuleBinding::default_export(), *n.src.clone()),
-                    );
-                }
-                ImportSpecifier::Namespace(namespace) => {
-                    self.0.insert(
-                        namespace.local.to_id(),
-                        (ModuleBinding::Namespace, *n.src.clone()),
-                    );
+        for item in import.specifiers {
+            let (import, local) = match item {
+                ImportSpecifier::Named(spec) => (
+                    spec.imported
+                        .unwrap_or_else(|| spec.local.clone().into())
+                        .into(),
+                    spec.local,
+                ),
+                ImportSpecifier::Default(spec) => (ModuleBinding::default_export(), spec.local),
+                ImportSpecifier::Namespace(spec) => (ModuleBinding::Namespace, spec.local),
+            };
+            self.imported_ident
+                .insert(local.to_id(), self.bindings.len());
+            self.bindings.push(
+                ImportBinding {
+                    import,
+                    alias: Some(local),
+                    from: from.clone(),
                 }
+                .into(),
+            );
+        }
+    }
+    /// `import 'mod'` only loads the module, the namespace is not used.
+    fn phantom_import(&mut self, from: Str) {
+        self.phantom_import_binding_id += 1;
+        self.bindings.push(
+            ImportBinding {
+                import: ModuleBinding::Namespace,
+                from,
+                alias: Some(private_ident!(format!(
+                    "import_{}",
+                    self.phantom_import_binding_id
+                ))),
             }
+            .into(),
+        );
+    }
+    /// `export { local as alias }` of an imported binding is a re-export of the import.
+    fn reexport(&self, import_index: usize, alias: ModuleExportName) -> Binding {
+        let Binding::Import(import) = &self.bindings[import_index] else {
+            unreachable!("imported_ident only points to imports")
+        };
+        ExportBinding {
+            export: import.import.clone(),
+            alias: Some(alias),
+            from: Some(import.from.clone()),
         }
+        .into()
     }
-}
-
-struct ScannerSecondPass {
-    bindings: Vec<Binding>,
-    phantom_import_binding_id: u32,
-    imported_ident: HashMap<Id, (ModuleBinding, Str)>,
-    live_export_tracing_bindings: Vec<LiveExportTracingBinding>,
-}
-impl Visit for ScannerSecondPass {
-    /// Scan all import/export bindings inside a ModuleDecl
-    fn visit_module_decl(&mut self, decl: &ModuleDecl) {
-        match decl {
-            ModuleDecl::Import(import) if !import.type_only => {
-                if import.specifiers.is_empty() {
-                    self.phantom_import_binding_id += 1;
-                    self.bindings.push(
-                        ImportBinding {
-                            import: ModuleBinding::Namespace,
-                            from: *import.src.clone(),
-                            alias: Some(private_ident!(format!(
-                                "import_{}",
-                                self.phantom_import_binding_id
-                            ))),
-                        }
-                        .into(),
-                    )
-                }
-                for item in &import.specifiers {
-                    match item {
-                        ImportSpecifier::Named(spec) => {
-                            if import.type_only {
-                                continue;
-                            }
-                            let local_ident = spec.local.clone();
-                            let imported_ident = if let Some(imported) = &spec.imported {
-                                imported.clone()
-                            } else {
-                                ModuleExportName::Ident(spec.local.clone())
-                            };
-                            self.bindings.push(
-                                ImportBinding {
-                                    import: imported_ident.into(),
-                                    alias: Some(local_ident),
-                                    from: *import.src.clone(),
-                                }
-                                .into(),
-                            );
-                        }
-                        ImportSpecifier::Default(spec) => self.bindings.push(
-                            ImportBinding {
-                                import: ModuleBinding::default_export(),
-                                alias: Some(spec.local.clone()),
-                                from: *import.src.clone(),
-                            }
-                            .into(),
-                        ),
-                        ImportSpecifier::Namespace(spec) => self.bindings.push(
-                            ImportBinding {
-                                import: ModuleBinding::Namespace,
-                                alias: Some(spec.local.clone()),
-                                from: *import.src.clone(),
-                            }
-                            .into(),
-                        ),
-                    }
-                }
+    /// Imports are hoisted, exports scanned before the import of their binding are fixed up here.
+    fn finish(&mut self) {
+        for (index, id, alias) in take(&mut self.pending_exports) {
+            if let Some(&import_index) = self.imported_ident.get(&id) {
+                self.bindings[index] = self.reexport(import_index, alias);
             }
+        }
+    }
+    /// Scan all export bindings inside a ModuleDecl
+    fn scan_module_decl(&mut self, decl: &ModuleDecl) {
+        match decl {
             ModuleDecl::ExportDecl(export) => match &export.decl {
                 // export using x = expr is illegal
                 Decl::Using(_) => (),
                 Decl::Class(class) => {
                     self.bindings.push(ExportBinding::local(&class.ident));
-                    self.live_export_tracing_bindings
-                        .push(LiveExportTracingBinding::simple(&class.ident));
+                    self.trace_live_export(&class.ident, class.ident.clone().into());
                 }
                 Decl::Fn(f) => {
                     self.bindings.push(ExportBinding::local(&f.ident));
-                    self.live_export_tracing_bindings
-                        .push(LiveExportTracingBinding::simple(&f.ident));
+                    self.trace_live_export(&f.ident, f.ident.clone().into());
                 }
                 Decl::Var(var) => {
                     for decl in &var.decls {
@@ -159,27 +136,13 @@ impl Visit for ScannerSecondPass {
                             );
                         }
                         ExportSpecifier::Named(spec) => {
-                            let mut bindings_pushed = false;
-                            if let ModuleExportName::Ident(ident) = &spec.orig {
-                                let id = self.imported_ident.get(&ident.to_id());
-                                if let Some((binding, from)) = id {
-                                    assert!(export.src.is_none());
-                                    self.bindings.push(
-                                        ExportBinding {
-                                            export: binding.clone(),
-                                            alias: Some(
-                                                spec.exported
-                                                    .clone()
-                                                    .unwrap_or_else(|| ident.clone().into()),
-                                            ),
-                                            from: Some(from.clone()),
-                                        }
-                                        .into(),
-                                    );
-                                    bindings_pushed = true;
+                            let local = match &spec.orig {
+                                ModuleExportName::Ident(ident) if export.src.is_none() => {
+                                    Some(ident)
                                 }
-                            }
-                            if !bindings_pushed {
+                                _ => None,
+                            };
+                            let Some(local) = local else {
                                 self.bindings.push(
                                     ExportBinding {
                                         export: spec.orig.clone().into(),
@@ -187,23 +150,32 @@ impl Visit for ScannerSecondPass {
                                         from: export.src.clone().map(|from| *from),
                                     }
                                     .into(),
-                                )
-                            }
-                            if export.src.is_none() {
-                                if let ModuleExportName::Ident(local_name) = &spec.orig {
-                                    if let Some(export_name) = &spec.exported {
-                                        self.live_export_tracing_bindings.push(
-                                            LiveExportTracingBinding::complex(
-                                                local_name,
-                                                export_name,
-                                            ),
-                                        );
-                                    } else {
-                                        self.live_export_tracing_bindings
-                                            .push(LiveExportTracingBinding::simple(local_name));
+                                );
+                                continue;
+                            };
+                            let alias = spec
+                                .exported
+                                .clone()
+                                .unwrap_or_else(|| local.clone().into());
+                            if let Some(&import_index) = self.imported_ident.get(&local.to_id()) {
+                                self.bindings
+                                    .push(self.reexport(import_index, alias.clone()));
+                            } else {
+                                self.pending_exports.push((
+                                    self.bindings.len(),
+                                    local.to_id(),
+                                    alias.clone(),
+                                ));
+                                self.bindings.push(
+                                    ExportBinding {
+                                        export: local.clone().into(),
+                                        alias: spec.exported.clone(),
+                                        from: None,
                                     }
-                                }
+                                    .into(),
+                                );
                             }
+                            self.trace_live_export(local, alias);
                         }
                     }
                 }
@@ -216,11 +188,7 @@ impl Visit for ScannerSecondPass {
                 };
                 if let Some(local_ident) = local_ident {
                     let default_ident = Ident::new("default".into(), DUMMY_SP);
-                    self.live_export_tracing_bindings
-                        .push(LiveExportTracingBinding {
-                            local_ident: local_ident.clone(),
-                            export: ModuleExportName::Ident(default_ident.clone()),
-                        });
+                    self.trace_live_export(local_ident, default_ident.clone().into());
                     self.bindings.push(
                         ExportBinding {
                             from: None,
@@ -251,6 +219,25 @@ impl Visit for ScannerSecondPass {
                 );
             }
             ModuleDecl::ExportAll(export) => {
+                if let Some(names) = self.star_exports.remove(&*export.src.value) {
+                    // The module is still loaded when it provides no name.
+                    if names.is_empty() {
+                        self.phantom_import(*export.src.clone());
+                    }
+                    for name in names {
+                        self.bindings.push(
+                            ExportBinding {
+                                from: Some(*export.src.clone()),
+                                export: ModuleBinding::ModuleExportName(ModuleExportName::Str(
+                                    name.into(),
+                                )),
+                                alias: None,
+                            }
+                            .into(),
+                        );
+                    }
+                    return;
+                }
                 self.bindings.push(
                     ExportBinding {
                         from: Some(*export.src.clone()),
@@ -262,18 +249,20 @@ impl Visit for ScannerSecondPass {
             }
             _ => {}
         };
-        decl.visit_children_with(self);
     }
-}
-
-impl ScannerSecondPass {
+    /// Assignments to `local` must be reflected on the export `export`.
+    fn trace_live_export(&mut self, local: &Ident, export: ModuleExportName) {
+        self.live_export_tracing_bindings
+            .entry(local.to_id())
+            .or_default()
+            .push(export);
+    }
     /// Scan all bindings inside a BindingPattern
     fn visit_pat_inner(&mut self, pat: &Pat) {
         match pat {
             Pat::Ident(id) => {
                 self.bindings.push(ExportBinding::simple(&id.id).into());
-                self.live_export_tracing_bindings
-                    .push(LiveExportTracingBinding::simple(&id.id));
+                self.trace_live_export(&id.id, id.id.clone().into());
             }
             Pat::Array(arr) => {
                 for elem in arr.elems.iter().flatten() {
@@ -288,8 +277,7 @@ impl ScannerSecondPass {
                         ObjectPatProp::Assign(assign) => {
                             self.bindings
                                 .push(ExportBinding::simple(&assign.key).into());
-                            self.live_export_tracing_bindings
-                                .push(LiveExportTracingBinding::simple(&assign.key));
+                            self.trace_live_export(&assign.key, assign.key.clone().into());
                         }
                         ObjectPatProp::Rest(RestPat { arg, .. }) => self.visit_pat_inner(arg),
                     }
@@ -303,21 +291,68 @@ impl ScannerSecondPass {
 }
 
 impl VirtualModuleRecordTransformer {
-    pub fn scan(&mut self, module: &Module) {
-        let mut scanner_first_pass = ScannerFirstPass(HashMap::new());
-        module.visit_with(&mut scanner_first_pass);
-
-        let mut scanner_second_pass = ScannerSecondPass {
-            imported_ident: scanner_first_pass.0,
+    /// Scan the bindings of the module in one pass over its top-level items.
+    ///
+    /// Import declarations are moved out of the module, the transformer drops them anyway.
+    pub fn scan(&mut self, module: &mut Module) {
+        let mut scanner = Scanner {
             bindings: vec![],
             phantom_import_binding_id: 0,
-            live_export_tracing_bindings: vec![],
+            imported_ident: HashMap::new(),
+            pending_exports: vec![],
+            live_export_tracing_bindings: HashMap::new(),
+            star_exports: take(&mut self.star_exports),
         };
-        module.visit_with(&mut scanner_second_pass);
+        let body = take(&mut module.body);
+        module.body.reserve(body.len());
+        for item in body {
+            match item {
+                ModuleItem::ModuleDecl(ModuleDecl::Import(import)) => {
+                    if !import.type_only {
+                        scanner.scan_import(import);
+                    }
+                }
+                item => {
+                    if let ModuleItem::ModuleDecl(decl) = &item {
+                        scanner.scan_module_decl(decl);
+                    }
+                    module.body.push(item);
+                }
+            }
+        }
+        scanner.finish();
+
+        self.bindings = scanner.bindings;
+        self.imported_ident = scanner.imported_ident;
+        self.local_resolved_bindings = scanner.live_export_tracing_bindings;
+        self.uses_top_level_await =
+            contains_top_level_await(module) || contains_top_level_await_using(module);
+    }
+}
+
+/// `contains_top_level_await` does not know `await using` declarations.
+fn contains_top_level_await_using(module: &Module) -> bool {
+    let mut finder = TopLevelAwaitUsing(false);
+    module.visit_with(&mut finder);
+    finder.0
+}
+
+struct TopLevelAwaitUsing(bool);
+impl Visit for TopLevelAwaitUsing {
+    noop_visit_type!();
 
-        self.bindings = scanner_second_pass.bindings;
-        self.imported_ident = scanner_second_pass.imported_ident;
-        self.local_resolved_bindings = scanner_second_pass.live_export_tracing_bindings;
-        self.uses_top_level_await = contains_top_level_await(module);
+    fn visit_using_decl(&mut self, n: &UsingDecl) {
+        self.0 |= n.is_await;
+        n.visit_children_with(self);
+    }
+    fn visit_function(&mut self, _: &Function) {}
+    fn visit_arrow_expr(&mut self, _: &ArrowExpr) {}
+    fn visit_class_member(&mut self, n: &ClassMember) {
+        // only computed keys are evaluated at the top level.
+        match n {
+            ClassMember::Method(ClassMethod { key, .. })
+            | ClassMember::ClassProp(ClassProp { key, .. }) => key.visit_with(self),
+            _ => {}
+        }
     }
 }
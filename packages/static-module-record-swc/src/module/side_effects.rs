@@ -0,0 +1,181 @@
+use swc_core::common::comments::CommentKind;
+use swc_core::common::Span;
+use swc_core::ecma::ast::*;
+
+use super::VirtualModuleRecordTransformer;
+
+impl VirtualModuleRecordTransformer {
+    /// The statements of `execute` only declare bindings, export them (`__.x = x`) and call
+    /// functions annotated with `/*#__PURE__*/`, so running `execute` is not observable unless an
+    /// export is read.
+    ///
+    /// Like bundlers, errors thrown by reading a binding in its TDZ or by `extends` are ignored.
+    pub fn is_side_effect_free(&self, stmts: &[Stmt]) -> bool {
+        stmts.iter().all(|stmt| self.is_pure_stmt(stmt))
+    }
+    pub(super) fn is_pure_stmt(&self, stmt: &Stmt) -> bool {
+        match stmt {
+            Stmt::Empty(_) | Stmt::Decl(Decl::Fn(_)) => true,
+            Stmt::Decl(Decl::Class(decl)) => self.is_pure_class(&decl.class),
+            // Destructuring runs iterators and getters.
+            Stmt::Decl(Decl::Var(decl)) => decl.decls.iter().all(|decl| {
+                decl.name.is_ident()
+                    && decl
+                        .init
+                        .as_deref()
+                        .map_or(true, |init| self.is_pure_expr(init))
+            }),
+            Stmt::Expr(ExprStmt { expr, .. }) => self.is_pure_expr(expr),
+            _ => false,
+        }
+    }
+    pub(super) fn is_pure_expr(&self, expr: &Expr) -> bool {
+        match expr {
+            Expr::Lit(_) | Expr::Ident(_) | Expr::Fn(_) | Expr::Arrow(_) => true,
+            Expr::Class(class) => self.is_pure_class(&class.class),
+            Expr::Paren(ParenExpr { expr, .. }) => self.is_pure_expr(expr),
+            // Other values are converted to strings.
+            Expr::Tpl(tpl) => tpl.exprs.iter().all(|expr| expr.is_lit()),
+            Expr::Array(array) => array
+                .elems
+                .iter()
+                .flatten()
+                .all(|elem| elem.spread.is_none() && self.is_pure_expr(&elem.expr)),
+            Expr::Object(object) => object.props.iter().all(|prop| match prop {
+                PropOrSpread::Prop(prop) => match &**prop {
+                    Prop::Shorthand(_) => true,
+                    Prop::KeyValue(KeyValueProp { key, value }) => {
+                        is_pure_key(key) && self.is_pure_expr(value)
+                    }
+                    Prop::Method(MethodProp { key, .. })
+                    | Prop::Getter(GetterProp { key, .. })
+                    | Prop::Setter(SetterProp { key, .. }) => is_pure_key(key),
+                    Prop::Assign(_) => false,
+                },
+                PropOrSpread::Spread(_) => false,
+            }),
+            Expr::Unary(UnaryExpr { op, arg, .. }) => match op {
+                op!("void") | op!("!") | op!("typeof") => self.is_pure_expr(arg),
+                op!("delete") => false,
+                // Other operators convert objects to numbers.
+                _ => arg.is_lit(),
+            },
+            Expr::Bin(BinExpr {
+                op, left, right, ..
+            }) => match op {
+                op!("===") | op!("!==") | op!("&&") | op!("||") | op!("??") => {
+                    self.is_pure_expr(left) && self.is_pure_expr(right)
+                }
+                op!("in") | op!("instanceof") => false,
+                _ => left.is_lit() && right.is_lit(),
+            },
+            Expr::Cond(CondExpr {
+                test, cons, alt, ..
+            }) => self.is_pure_expr(test) && self.is_pure_expr(cons) && self.is_pure_expr(alt),
+            Expr::Seq(SeqExpr { exprs, .. }) => exprs.iter().all(|expr| self.is_pure_expr(expr)),
+            Expr::Member(member) => self.is_pure_member(member),
+            // __.x = x
+            Expr::Assign(AssignExpr {
+                op: op!("="),
+                left: PatOrExpr::Expr(left),
+                right,
+                ..
+            }) => {
+                matches!(&**left, Expr::Member(MemberExpr { obj, .. }) if self.is_module_env_record(obj))
+                    && self.is_pure_expr(right)
+            }
+            Expr::Call(CallExpr {
+                span,
+                callee: Callee::Expr(_),
+                args,
+                ..
+            }) => self.is_pure_annotated(*span) && self.is_pure_args(args),
+            Expr::New(NewExpr { span, args, .. }) => {
+                self.is_pure_annotated(*span)
+                    && args.as_deref().map_or(true, |args| self.is_pure_args(args))
+            }
+            _ => false,
+        }
+    }
+    fn is_pure_args(&self, args: &[ExprOrSpread]) -> bool {
+        args.iter()
+            .all(|arg| arg.spread.is_none() && self.is_pure_expr(&arg.expr))
+    }
+    /// Imports (`__.x`) and the `{ default: ... }.default` of anonymous default exports.
+    fn is_pure_member(&self, member: &MemberExpr) -> bool {
+        let pure_prop = match &member.prop {
+            MemberProp::Ident(_) => true,
+            MemberProp::Computed(ComputedPropName { expr, .. }) => expr.is_lit(),
+            MemberProp::PrivateName(_) => false,
+        };
+        pure_prop
+            && match &*member.obj {
+                obj if self.is_module_env_record(obj) => true,
+                Expr::Object(object) => {
+                    object
+                        .props
+                        .iter()
+                        .all(|prop| matches!(prop, PropOrSpread::Prop(prop) if prop.is_key_value()))
+                        && self.is_pure_expr(&member.obj)
+                }
+                _ => false,
+            }
+    }
+    /// Static members and computed keys are evaluated when the class is defined.
+    fn is_pure_class(&self, class: &Class) -> bool {
+        class.decorators.is_empty()
+            && class
+                .super_class
+                .as_deref()
+                .map_or(true, |super_class| self.is_pure_expr(super_class))
+            && class.body.iter().all(|member| match member {
+                ClassMember::Constructor(_)
+                | ClassMember::PrivateMethod(_)
+                | ClassMember::TsIndexSignature(_)
+                | ClassMember::Empty(_) => true,
+                ClassMember::Method(method) => {
+                    method.function.decorators.is_empty() && is_pure_key(&method.key)
+                }
+                ClassMember::ClassProp(prop) => {
+                    prop.decorators.is_empty()
+                        && is_pure_key(&prop.key)
+                        && (!prop.is_static
+                            || prop
+                                .value
+                                .as_deref()
+                                .map_or(true, |value| self.is_pure_expr(value)))
+                }
+                ClassMember::PrivateProp(prop) => {
+                    !prop.is_static
+                        || prop
+                            .value
+                            .as_deref()
+                            .map_or(true, |value| self.is_pure_expr(value))
+                }
+                ClassMember::StaticBlock(_) | ClassMember::AutoAccessor(_) => false,
+            })
+    }
+    fn is_module_env_record(&self, expr: &Expr) -> bool {
+        matches!(expr, Expr::Ident(ident) if ident.to_id() == self.module_env_record_ident.to_id())
+    }
+    /// `/*#__PURE__*/` or `/*@__PURE__*/` before a call or `new`.
+    fn is_pure_annotated(&self, span: Span) -> bool {
+        let Some(comments) = &self.comments else {
+            return false;
+        };
+        comments.get_leading(span.lo).is_some_and(|comments| {
+            comments.iter().any(|comment| {
+                comment.kind == CommentKind::Block
+                    && matches!(comment.text.trim(), "#__PURE__" | "@__PURE__")
+            })
+        })
+    }
+}
+
+/// Computed keys other than literals are converted to property keys.
+fn is_pure_key(key: &PropName) -> bool {
+    match key {
+        PropName::Computed(ComputedPropName { expr, .. }) => expr.is_lit(),
+        _ => true,
+    }
+}
@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+
+use swc_core::common::{sync::Lrc, FileName, SourceMap};
+use swc_core::ecma::ast::*;
+use swc_core::ecma::parser::{parse_file_as_module, Syntax};
+use swc_core::ecma::utils::drop_span;
+use swc_core::ecma::visit::{VisitMut, VisitMutWith};
+
+/// Parse the template of `Template::Custom`.
+pub(super) fn parse_template(template: &str) -> Result<Module, String> {
+    let cm: Lrc<SourceMap> = Default::default();
+    let fm = cm.new_source_file(FileName::Anon, template.into());
+    let module = parse_file_as_module(
+        &fm,
+        Syntax::Es(Default::default()),
+        EsVersion::latest(),
+        None,
+        &mut vec![],
+    )
+    .map_err(|err| format!("The custom template is not a module: {}", err.kind().msg()))?;
+    Ok(drop_span(module))
+}
+
+/// Splice `record` in place of `$RECORD` of a copy of the parsed custom template.
+///
+/// The other placeholders are replaced by the string of `values`, a placeholder without a value
+/// is an error. Placeholders are identifiers, so they are not replaced inside strings.
+pub fn custom_template(
+    template: &Module,
+    record: Expr,
+    values: HashMap<&str, Option<String>>,
+) -> Result<Vec<ModuleItem>, String> {
+    let mut module = template.clone();
+    let mut placeholders = Placeholders {
+        record: Some(record),
+        records: 0,
+        values,
+        error: None,
+    };
+    module.visit_mut_with(&mut placeholders);
+    if let Some(error) = placeholders.error {
+        return Err(error);
+    }
+    if placeholders.records != 1 {
+        return Err("`$RECORD` must appear exactly once in the custom template.".to_string());
+    }
+    Ok(module.body)
+}
+
+struct Placeholders<'a> {
+    record: Option<Expr>,
+    records: usize,
+    values: HashMap<&'a str, Option<String>>,
+    error: Option<String>,
+}
+
+impl VisitMut for Placeholders<'_> {
+    fn visit_mut_expr(&mut self, n: &mut Expr) {
+        let Expr::Ident(ident) = n else {
+            return n.visit_mut_children_with(self);
+        };
+        if &*ident.sym == "$RECORD" {
+            self.records += 1;
+            if let Some(record) = self.record.take() {
+                *n = record;
+            }
+        } else if let Some(value) = self.values.get(&*ident.sym) {
+            match value {
+                Some(value) => *n = value.as_str().into(),
+                None => {
+                    self.error.get_or_insert_with(|| {
+                        format!("`{}` is not available for this module.", ident.sym)
+                    });
+                }
+            }
+        }
+    }
+}
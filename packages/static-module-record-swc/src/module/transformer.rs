@@ -1,15 +1,17 @@
 use std::ops::Deref;
 
-use swc_core::common::DUMMY_SP;
+use swc_core::common::{Span, DUMMY_SP};
 use swc_core::ecma::ast::*;
 use swc_core::ecma::atoms::js_word;
-use swc_core::ecma::utils::{quote_ident, ExprFactory};
+use swc_core::ecma::utils::{quote_ident, undefined, ExprFactory};
 use swc_core::ecma::visit::{Fold, FoldWith};
 
 use super::{
     codegen::{assign_prop, prop_access, undefined_this_wrapper},
+    direct_eval::collect_direct_eval_scopes,
     VirtualModuleRecordTransformer,
 };
+use crate::utils::{emit_error, key_value};
 
 impl VirtualModuleRecordTransformer {
     pub fn fold_module_item_to_multiple(&mut self, item: ModuleItem) -> Vec<Stmt> {
@@ -49,11 +51,8 @@ impl VirtualModuleRecordTransformer {
                                 .into(),
                             )
                         } else {
-                            vec![expr_to_stmt(assign_prop(
-                                self.module_env_record_ident.clone(),
-                                quote_ident!("default").into(),
-                                Box::new(node.fold_children_with(self).into()),
-                            ))]
+                            let expr = node.fold_children_with(self).into();
+                            self.export_default(expr, true)
                         }
                     }
                     DefaultDecl::Fn(node) => {
@@ -67,30 +66,49 @@ impl VirtualModuleRecordTransformer {
                                 .into(),
                             )
                         } else {
-                            vec![expr_to_stmt(assign_prop(
-                                self.module_env_record_ident.clone(),
-                                quote_ident!("default").into(),
-                                Box::new(node.fold_children_with(self).into()),
-                            ))]
+                            let expr = node.fold_children_with(self).into();
+                            self.export_default(expr, true)
                         }
                     }
                     DefaultDecl::TsInterfaceDecl(_) => unimplemented!(),
                 },
                 // export default expr => env.default = expr
-                ModuleDecl::ExportDefaultExpr(node) => vec![expr_to_stmt(assign_prop(
-                    self.module_env_record_ident.clone(),
-                    quote_ident!("default").into(),
-                    node.expr.fold_children_with(self),
-                ))],
+                ModuleDecl::ExportDefaultExpr(node) => {
+                    let expr = node.expr.fold_children_with(self);
+                    let named = is_anonymous_function_definition(&expr);
+                    self.export_default(*expr, named)
+                }
                 // export * from './foo' => No emit
                 ModuleDecl::ExportAll(_) => vec![],
                 ModuleDecl::TsImportEquals(_) => unimplemented!(),
                 ModuleDecl::TsExportAssignment(_) => unimplemented!(),
                 ModuleDecl::TsNamespaceExport(_) => unimplemented!(),
             },
+            // only resources declared at the top level are disposed at the end of the evaluation.
+            ModuleItem::Stmt(Stmt::Decl(Decl::Using(using))) => self.fold_top_level_using(*using),
             ModuleItem::Stmt(stmt) => self.fold_stmt_to_multiple(stmt),
         }
     }
+    /// `__.default = expr`, `named` gives anonymous functions and classes the name `default`.
+    ///
+    /// Only side effects of `expr` are kept when the default export is not used.
+    fn export_default(&self, expr: Expr, named: bool) -> Vec<Stmt> {
+        if !self.is_used_export("default") {
+            return match self.is_pure_expr(&expr) {
+                true => vec![],
+                false => vec![expr_to_stmt(expr)],
+            };
+        }
+        vec![expr_to_stmt(assign_prop(
+            self.module_env_record_ident.clone(),
+            quote_ident!("default").into(),
+            if named {
+                named_default(expr)
+            } else {
+                Box::new(expr)
+            },
+        ))]
+    }
     fn fold_stmt_to_multiple(&mut self, node: Stmt) -> Vec<Stmt> {
         match node {
             Stmt::For(node) => {
@@ -111,9 +129,9 @@ impl VirtualModuleRecordTransformer {
                     vec![node.fold_children_with(self).into()]
                 } else {
                     vec![ForStmt {
-                        init: node.init.fold_children_with(self),
-                        test: node.test.map(|x| x.fold_children_with(self)),
-                        update: node.update.map(|x| x.fold_children_with(self)),
+                        init: node.init.fold_with(self),
+                        test: node.test.fold_with(self),
+                        update: node.update.fold_with(self),
                         body: prepend_stmt(
                             *node.body.fold_children_with(self),
                             exprs_to_stmt(tracing),
@@ -133,7 +151,7 @@ impl VirtualModuleRecordTransformer {
                             }
                         }
                     }
-                    ForHead::UsingDecl(_) => (),
+                    ForHead::UsingDecl(using) => unsupported_using(using.span),
                     ForHead::Pat(pat) => {
                         self.trace_live_export_pat(pat, &mut tracing);
                     }
@@ -146,8 +164,8 @@ impl VirtualModuleRecordTransformer {
                             *node.body.fold_children_with(self),
                             exprs_to_stmt(tracing),
                         ),
-                        left: node.left.fold_children_with(self),
-                        right: node.right.fold_children_with(self),
+                        left: node.left.fold_with(self),
+                        right: node.right.fold_with(self),
                         span: node.span,
                     }
                     .into()]
@@ -165,7 +183,7 @@ impl VirtualModuleRecordTransformer {
                             }
                         }
                     }
-                    ForHead::UsingDecl(_) => (),
+                    ForHead::UsingDecl(using) => unsupported_using(using.span),
                     ForHead::Pat(pat) => {
                         self.trace_live_export_pat(pat, &mut tracing);
                     }
@@ -178,8 +196,8 @@ impl VirtualModuleRecordTransformer {
                             *node.body.fold_children_with(self),
                             exprs_to_stmt(tracing),
                         ),
-                        left: node.left.fold_children_with(self),
-                        right: node.right.fold_children_with(self),
+                        left: node.left.fold_with(self),
+                        right: node.right.fold_with(self),
                         is_await: node.is_await,
                         span: node.span,
                     }
@@ -192,7 +210,7 @@ impl VirtualModuleRecordTransformer {
             _ => vec![node.fold_children_with(self)],
         }
     }
-    fn fold_declaration_to_multiple(&mut self, decl: Decl) -> Vec<Stmt> {
+    pub fn fold_declaration_to_multiple(&mut self, decl: Decl) -> Vec<Stmt> {
         let mut tracing = vec![];
         match &decl {
             Decl::Class(class) => self.trace_live_export_ident(&class.ident, &mut tracing),
@@ -202,7 +220,8 @@ impl VirtualModuleRecordTransformer {
                     self.trace_live_export_pat(&item.name, &mut tracing);
                 }
             }
-            Decl::Using(_) => (),
+            // blocks and the module body have converted theirs.
+            Decl::Using(decl) => unsupported_using(decl.span),
             Decl::TsInterface(_) => (),
             Decl::TsTypeAlias(_) => (),
             Decl::TsEnum(_) => (),
@@ -244,33 +263,26 @@ impl VirtualModuleRecordTransformer {
         }
     }
     fn trace_live_export_ident(&self, local_ident: &Ident, tracing: &mut Vec<Expr>) {
-        let mut need_init_expr = false;
+        let Some(exports) = self.local_resolved_bindings.get(&local_ident.to_id()) else {
+            return;
+        };
         let init_expr: Expr = local_ident.clone().into();
-        let assign = self
-            .local_resolved_bindings
-            .iter()
-            .filter(|x| x.local_ident.to_id() == local_ident.to_id())
-            .fold(init_expr, |expr, x| {
-                need_init_expr = true;
-                match &x.export {
-                    ModuleExportName::Ident(ident) => assign_prop(
-                        self.module_env_record_ident.clone(),
-                        MemberProp::Ident(ident.clone()),
-                        Box::new(expr),
-                    ),
-                    ModuleExportName::Str(str) => assign_prop(
-                        self.module_env_record_ident.clone(),
-                        MemberProp::Computed(ComputedPropName {
-                            span: DUMMY_SP,
-                            expr: Box::new(str.clone().into()),
-                        }),
-                        Box::new(expr),
-                    ),
-                }
-            });
-        if need_init_expr {
-            tracing.push(assign);
-        }
+        let assign = exports.iter().fold(init_expr, |expr, export| match export {
+            ModuleExportName::Ident(ident) => assign_prop(
+                self.module_env_record_ident.clone(),
+                MemberProp::Ident(ident.clone()),
+                Box::new(expr),
+            ),
+            ModuleExportName::Str(str) => assign_prop(
+                self.module_env_record_ident.clone(),
+                MemberProp::Computed(ComputedPropName {
+                    span: DUMMY_SP,
+                    expr: Box::new(str.clone().into()),
+                }),
+                Box::new(expr),
+            ),
+        });
+        tracing.push(assign);
     }
     fn need_ident_fold(&self, id: &Ident) -> bool {
         let is_arguments = self.may_include_implicit_arguments && id.sym == js_word!("arguments");
@@ -301,6 +313,15 @@ impl VirtualModuleRecordTransformer {
         })
         .unwrap_or_else(|| id.clone().into())
     }
+    /// Field initializers and static blocks bind `this` but have no `arguments`.
+    fn fold_class_body<T: FoldWith<Self>>(&mut self, n: T) -> T {
+        let old = (self.may_include_implicit_arguments, self.binds_this);
+        self.may_include_implicit_arguments = false;
+        self.binds_this = true;
+        let n = n.fold_children_with(self);
+        (self.may_include_implicit_arguments, self.binds_this) = old;
+        n
+    }
     fn is_unresolved(&self, id: &Ident) -> bool {
         id.span.ctxt == self.unresolved
     }
@@ -312,17 +333,65 @@ impl VirtualModuleRecordTransformer {
 // https://rustdoc.swc.rs/swc_ecma_visit/trait.Fold.html
 impl Fold for VirtualModuleRecordTransformer {
     fn fold_function(&mut self, n: Function) -> Function {
-        let old = self.may_include_implicit_arguments;
+        let old = (self.may_include_implicit_arguments, self.binds_this);
         self.may_include_implicit_arguments = true;
+        self.binds_this = true;
         let n = n.fold_children_with(self);
-        self.may_include_implicit_arguments = old;
+        (self.may_include_implicit_arguments, self.binds_this) = old;
         n
     }
-    fn fold_class(&mut self, n: Class) -> Class {
-        let old = self.may_include_implicit_arguments;
-        self.may_include_implicit_arguments = false;
+    // `extends`, decorators and computed keys of a class are evaluated in the outer scope,
+    // only the member bodies bind `this`.
+    fn fold_constructor(&mut self, n: Constructor) -> Constructor {
+        let old = (self.may_include_implicit_arguments, self.binds_this);
+        self.may_include_implicit_arguments = true;
+        self.binds_this = true;
         let n = n.fold_children_with(self);
-        self.may_include_implicit_arguments = old;
+        (self.may_include_implicit_arguments, self.binds_this) = old;
+        n
+    }
+    fn fold_class_prop(&mut self, mut n: ClassProp) -> ClassProp {
+        let value = n.value.take();
+        let n = n.fold_children_with(self);
+        ClassProp {
+            value: self.fold_class_body(value),
+            ..n
+        }
+    }
+    fn fold_private_prop(&mut self, mut n: PrivateProp) -> PrivateProp {
+        let value = n.value.take();
+        let n = n.fold_children_with(self);
+        PrivateProp {
+            value: self.fold_class_body(value),
+            ..n
+        }
+    }
+    fn fold_auto_accessor(&mut self, mut n: AutoAccessor) -> AutoAccessor {
+        let value = n.value.take();
+        let n = n.fold_children_with(self);
+        AutoAccessor {
+            value: self.fold_class_body(value),
+            ..n
+        }
+    }
+    fn fold_static_block(&mut self, n: StaticBlock) -> StaticBlock {
+        self.fold_class_body(n)
+    }
+    // Getters and setters of object literals are not `Function`s.
+    fn fold_getter_prop(&mut self, n: GetterProp) -> GetterProp {
+        let old = (self.may_include_implicit_arguments, self.binds_this);
+        self.may_include_implicit_arguments = true;
+        self.binds_this = true;
+        let n = n.fold_children_with(self);
+        (self.may_include_implicit_arguments, self.binds_this) = old;
+        n
+    }
+    fn fold_setter_prop(&mut self, n: SetterProp) -> SetterProp {
+        let old = (self.may_include_implicit_arguments, self.binds_this);
+        self.may_include_implicit_arguments = true;
+        self.binds_this = true;
+        let n = n.fold_children_with(self);
+        (self.may_include_implicit_arguments, self.binds_this) = old;
         n
     }
     fn fold_callee(&mut self, n: Callee) -> Callee {
@@ -412,6 +481,19 @@ impl Fold for VirtualModuleRecordTransformer {
                 }
             }
             Expr::Ident(id) => self.fold_ident_inner(&id, false),
+            Expr::Call(call) if self.is_direct_eval(&call) => self.fold_direct_eval(call),
+            Expr::Call(call) if call.callee.is_import() => {
+                if let Some(Expr::Lit(Lit::Str(specifier))) = call.args.first().map(|x| &*x.expr) {
+                    self.dynamic_imports.push(specifier.value.to_string());
+                }
+                call.fold_children_with(self).into()
+            }
+            // `this` of a module is undefined, not the receiver the host calls `execute` with.
+            Expr::This(this) if !self.binds_this => ParenExpr {
+                span: this.span,
+                expr: undefined(this.span),
+            }
+            .into(),
             Expr::MetaProp(meta) if meta.kind == MetaPropKind::ImportMeta => {
                 self.uses_import_meta = true;
                 Expr::Member(MemberExpr {
@@ -451,20 +533,27 @@ impl Fold for VirtualModuleRecordTransformer {
             n.fold_children_with(self)
         }
     }
-    fn fold_module(&mut self, module: Module) -> Module {
-        self.scan(&module);
+    fn fold_module(&mut self, mut module: Module) -> Module {
+        // Before `scan` takes the star exports.
+        self.hash = self.record_hash();
+        if self.config.direct_eval {
+            self.direct_eval_scopes = collect_direct_eval_scopes(&module, self.unresolved);
+        }
+        self.scan(&mut module);
+        self.drop_unused_exports();
         let module = module.fold_children_with(self);
-        self.codegen(
-            module
-                .body
-                .into_iter()
-                .map(|x| {
-                    x.stmt()
-                        .expect("all imports/exports should be converted into statement.")
-                })
-                .collect(),
-            self,
-        )
+        let stmts = module
+            .body
+            .into_iter()
+            .map(|x| {
+                x.stmt()
+                    .expect("all imports/exports should be converted into statement.")
+            })
+            .collect();
+        let stmts = self.dispose_at_end(stmts);
+        let stmts = self.drop_unused_declarations(stmts);
+        self.side_effect_free = self.is_side_effect_free(&stmts);
+        self.codegen(stmts)
     }
     fn fold_module_items(&mut self, items: Vec<ModuleItem>) -> Vec<ModuleItem> {
         items
@@ -473,16 +562,21 @@ impl Fold for VirtualModuleRecordTransformer {
             .map(|x| x.into())
             .collect()
     }
+    fn fold_block_stmt(&mut self, n: BlockStmt) -> BlockStmt {
+        self.fold_block_with_using(n)
+    }
     fn fold_stmts(&mut self, stmt: Vec<Stmt>) -> Vec<Stmt> {
         stmt.into_iter()
             .flat_map(|x| self.fold_stmt_to_multiple(x))
             .collect()
     }
     fn fold_pat(&mut self, pat: Pat) -> Pat {
-        if let Pat::Ident(ident) = pat {
-            Pat::Expr(self.fold_ident_inner(&ident, false).into())
-        } else {
-            pat.fold_children_with(self)
+        // Binding identifiers must stay Pat::Ident, otherwise hygiene does not see the declaration.
+        match pat {
+            Pat::Ident(ident) if self.need_ident_fold(&ident) => {
+                Pat::Expr(self.fold_ident_inner(&ident, false).into())
+            }
+            pat => pat.fold_children_with(self),
         }
     }
     fn fold_object_pat_prop(&mut self, n: ObjectPatProp) -> ObjectPatProp {
@@ -501,6 +595,39 @@ impl Fold for VirtualModuleRecordTransformer {
     }
 }
 
+/// `export default function () {}` names the function `default`, but assigning it to `__.default`
+/// does not. The property name of an object literal does:
+///
+/// { default: function () {} }.default
+fn named_default(expr: Expr) -> Box<Expr> {
+    Box::new(
+        ObjectLit {
+            span: DUMMY_SP,
+            props: vec![key_value("default".into(), expr)],
+        }
+        .make_member(quote_ident!("default")),
+    )
+}
+
+/// Functions, arrow functions and classes without a name, also in parentheses.
+fn is_anonymous_function_definition(expr: &Expr) -> bool {
+    match expr {
+        Expr::Paren(paren) => is_anonymous_function_definition(&paren.expr),
+        Expr::Fn(FnExpr { ident: None, .. })
+        | Expr::Class(ClassExpr { ident: None, .. })
+        | Expr::Arrow(_) => true,
+        _ => false,
+    }
+}
+
+/// `using` in a `for` head or a `switch` case is not converted.
+fn unsupported_using(span: Span) {
+    emit_error(
+        span,
+        "`using` declarations are only supported in blocks and at the top level.",
+    );
+}
+
 fn expr_to_stmt(expr: Expr) -> Stmt {
     expr.into_stmt()
 }
@@ -508,8 +635,8 @@ fn exprs_to_stmt(expr: Vec<Expr>) -> Vec<Stmt> {
     expr.into_iter().map(expr_to_stmt).collect()
 }
 fn prepend_stmt(stmt: Stmt, mut insert_before: Vec<Stmt>) -> Box<Stmt> {
-    if let Some(block) = stmt.as_block() {
-        insert_before.append(&mut block.stmts.clone());
+    if let Stmt::Block(mut block) = stmt {
+        insert_before.append(&mut block.stmts);
         Box::new(
             BlockStmt {
                 span: DUMMY_SP,
@@ -1,6 +1,19 @@
-use swc_core::common::DUMMY_SP;
+/// Rewrite the sloppy mode code of scripts into strict mode code.
+mod sloppy;
+/// Rewrite `with` statements.
+mod with;
+
+use std::collections::HashSet;
+use std::mem::replace;
+
+use swc_core::common::{util::take::Take, Mark, SyntaxContext, DUMMY_SP};
 use swc_core::ecma::ast::*;
-use swc_core::ecma::visit::Fold;
+use swc_core::ecma::utils::{find_pat_ids, quote_ident, undefined, ExprFactory};
+use swc_core::ecma::visit::{
+    noop_visit_mut_type, noop_visit_type, Fold, Visit, VisitMut, VisitMutWith, VisitWith,
+};
+
+use crate::utils::key_value;
 
 pub struct ErrorTransformer {
     pub msg: String,
@@ -31,3 +44,340 @@ impl Fold for ErrorTransformer {
         }
     }
 }
+
+/// Convert a classic script into a module that behaves like the script in the global scope.
+///
+/// Top-level `var` and function declarations of a script are properties of globalThis, so they are
+/// turned into unresolved identifiers, which the transformer reads and writes through globalThis.
+/// `let`, `const` and `class` declarations stay local to the record instead of going to the global
+/// lexical scope of the realm. Top-level `this` is globalThis.
+///
+/// The record is strict mode code, see [`sloppy::sloppy_mode`] for how the sloppy mode code of the script
+/// keeps its meaning.
+pub fn script_to_module(mut script: Script, unresolved_mark: Mark) -> Module {
+    let unresolved = SyntaxContext::empty().apply_mark(unresolved_mark);
+    let mut callees = Default::default();
+    if !has_use_strict(&script.body) {
+        callees = sloppy::sloppy_mode(&mut script.body, unresolved);
+    }
+    let mut functions = vec![];
+    let mut vars = VarNames::default();
+    for stmt in &script.body {
+        match stmt {
+            Stmt::Decl(Decl::Fn(f)) => functions.push(f.ident.to_id()),
+            _ => stmt.visit_with(&mut vars),
+        }
+    }
+    let mut global_scope = GlobalScope {
+        globals: functions.iter().chain(&vars.0).cloned().collect(),
+        unresolved,
+        in_function: false,
+        in_var_scope: false,
+    };
+
+    let global_this = || Ident::new("globalThis".into(), DUMMY_SP.with_ctxt(unresolved));
+    let global = |(sym, _): &Id| Ident::new(sym.clone(), DUMMY_SP.with_ctxt(unresolved));
+    // "x" in globalThis || Object.defineProperty(globalThis, "x", { ... })
+    //
+    // Global `var` and function bindings are not configurable, and never overwrite existing ones.
+    let mut names = functions.clone();
+    names.extend(vars.0.into_iter().filter(|id| !functions.contains(id)));
+    let bindings: Vec<Stmt> = names
+        .iter()
+        .map(|(sym, _)| {
+            let descriptor = ObjectLit {
+                span: DUMMY_SP,
+                props: vec![
+                    key_value("value".into(), *undefined(DUMMY_SP)),
+                    key_value("writable".into(), true.into()),
+                    key_value("enumerable".into(), true.into()),
+                    key_value("configurable".into(), false.into()),
+                ],
+            };
+            let define = Ident::new("Object".into(), DUMMY_SP.with_ctxt(unresolved))
+                .make_member(quote_ident!("defineProperty"))
+                .as_call(
+                    DUMMY_SP,
+                    vec![
+                        global_this().as_arg(),
+                        Expr::from(&**sym).as_arg(),
+                        descriptor.as_arg(),
+                    ],
+                );
+            Expr::Bin(BinExpr {
+                span: DUMMY_SP,
+                op: op!("||"),
+                left: Box::new(Expr::Bin(BinExpr {
+                    span: DUMMY_SP,
+                    op: op!("in"),
+                    left: Box::new(Expr::from(&**sym)),
+                    right: Box::new(global_this().into()),
+                })),
+                right: Box::new(define),
+            })
+            .into_stmt()
+        })
+        .collect();
+    // Functions are initialized before the script runs.
+    let mut function_inits = Vec::with_capacity(functions.len());
+    let mut stmts = Vec::with_capacity(script.body.len());
+    for mut stmt in script.body {
+        let callee = match &stmt {
+            Stmt::Decl(Decl::Fn(f)) => callees.remove(&f.ident.to_id()),
+            _ => None,
+        };
+        stmt.visit_mut_with(&mut global_scope);
+        match stmt {
+            Stmt::Decl(Decl::Fn(FnDecl {
+                ident, function, ..
+            })) => function_inits.push(
+                Expr::from(FnExpr {
+                    // `arguments.callee` of sloppy mode code is the name of the function.
+                    ident: Some(callee.unwrap_or(ident.clone())),
+                    function,
+                })
+                .make_assign_to(op!("="), global(&ident.to_id()).into())
+                .into_stmt(),
+            ),
+            Stmt::Empty(_) => {}
+            stmt => stmts.push(stmt),
+        }
+    }
+    Module {
+        span: script.span,
+        body: bindings
+            .into_iter()
+            .chain(function_inits)
+            .chain(stmts)
+            .map(ModuleItem::Stmt)
+            .collect(),
+        shebang: script.shebang,
+    }
+}
+
+/// Collects the names declared by `var` outside of functions.
+#[derive(Default)]
+struct VarNames(Vec<Id>);
+impl Visit for VarNames {
+    noop_visit_type!();
+    fn visit_var_decl(&mut self, n: &VarDecl) {
+        if n.kind == VarDeclKind::Var {
+            for id in find_pat_ids::<_, Id>(&n.decls) {
+                if !self.0.contains(&id) {
+                    self.0.push(id);
+                }
+            }
+        }
+        n.visit_children_with(self);
+    }
+    fn visit_function(&mut self, _: &Function) {}
+    fn visit_arrow_expr(&mut self, _: &ArrowExpr) {}
+    fn visit_class(&mut self, _: &Class) {}
+    fn visit_getter_prop(&mut self, n: &GetterProp) {
+        n.key.visit_with(self);
+    }
+    fn visit_setter_prop(&mut self, n: &SetterProp) {
+        n.key.visit_with(self);
+    }
+}
+
+/// Move the global declarations of a script to globalThis.
+struct GlobalScope {
+    globals: HashSet<Id>,
+    unresolved: SyntaxContext,
+    /// Inside a function where `this` is not globalThis.
+    in_function: bool,
+    /// Inside a function or an arrow function, where `var` declares a local.
+    in_var_scope: bool,
+}
+impl GlobalScope {
+    /// var a = 1, b
+    /// =>
+    /// a = 1
+    fn var_to_assign(&self, var: &mut VarDecl) -> Option<Box<Expr>> {
+        let mut exprs: Vec<Box<Expr>> = var
+            .decls
+            .take()
+            .into_iter()
+            .filter_map(|decl| {
+                let init = decl.init?;
+                // var undefined = 1 does nothing but evaluate the initializer.
+                if let Pat::Ident(ident) = &decl.name {
+                    if self.globals.contains(&ident.to_id())
+                        && matches!(&*ident.sym, "undefined" | "NaN" | "Infinity")
+                    {
+                        return Some(init);
+                    }
+                }
+                Some(Box::new(Expr::Assign(AssignExpr {
+                    span: decl.span,
+                    op: op!("="),
+                    left: PatOrExpr::Pat(Box::new(decl.name)),
+                    right: init,
+                })))
+            })
+            .collect();
+        match exprs.len() {
+            0 => None,
+            1 => exprs.pop(),
+            _ => Some(Box::new(Expr::Seq(SeqExpr {
+                span: var.span,
+                exprs,
+            }))),
+        }
+    }
+}
+impl VisitMut for GlobalScope {
+    noop_visit_mut_type!();
+    fn visit_mut_ident(&mut self, n: &mut Ident) {
+        if self.globals.contains(&n.to_id()) {
+            n.span.ctxt = self.unresolved;
+        }
+    }
+    fn visit_mut_expr(&mut self, n: &mut Expr) {
+        if let Expr::This(this) = n {
+            if !self.in_function {
+                *n = Expr::Ident(Ident::new(
+                    "globalThis".into(),
+                    this.span.with_ctxt(self.unresolved),
+                ));
+            }
+            return;
+        }
+        n.visit_mut_children_with(self);
+        // `delete x` is a syntax error in strict mode code. It is `false` for a binding, and
+        // deletes the property of a global without throwing when it is not configurable.
+        if let Expr::Unary(UnaryExpr {
+            op: op!("delete"),
+            arg,
+            span,
+        }) = n
+        {
+            if let Expr::Ident(ident) = &**arg {
+                *n = if ident.span.ctxt == self.unresolved {
+                    Ident::new("Reflect".into(), DUMMY_SP.with_ctxt(self.unresolved))
+                        .make_member(quote_ident!("deleteProperty"))
+                        .as_call(
+                            *span,
+                            vec![
+                                Ident::new(
+                                    "globalThis".into(),
+                                    DUMMY_SP.with_ctxt(self.unresolved),
+                                )
+                                .as_arg(),
+                                Expr::from(&*ident.sym).as_arg(),
+                            ],
+                        )
+                } else {
+                    Expr::Lit(Lit::Bool(Bool {
+                        span: *span,
+                        value: false,
+                    }))
+                };
+            }
+        }
+    }
+    fn visit_mut_function(&mut self, n: &mut Function) {
+        let old = (self.in_function, self.in_var_scope);
+        (self.in_function, self.in_var_scope) = (true, true);
+        n.visit_mut_children_with(self);
+        (self.in_function, self.in_var_scope) = old;
+    }
+    /// The heritage and computed keys of a class are evaluated in the enclosing scope.
+    fn visit_mut_class(&mut self, n: &mut Class) {
+        n.decorators.visit_mut_with(self);
+        n.super_class.visit_mut_with(self);
+        let old = (self.in_function, self.in_var_scope);
+        for member in &mut n.body {
+            match member {
+                ClassMember::Method(method) => method.key.visit_mut_with(self),
+                ClassMember::ClassProp(prop) => prop.key.visit_mut_with(self),
+                ClassMember::AutoAccessor(AutoAccessor {
+                    key: Key::Public(key),
+                    ..
+                }) => key.visit_mut_with(self),
+                _ => {}
+            }
+            (self.in_function, self.in_var_scope) = (true, true);
+            match member {
+                ClassMember::Method(method) => method.function.visit_mut_with(self),
+                ClassMember::ClassProp(prop) => {
+                    prop.value.visit_mut_with(self);
+                    prop.decorators.visit_mut_with(self);
+                }
+                ClassMember::AutoAccessor(accessor) => {
+                    accessor.value.visit_mut_with(self);
+                    accessor.decorators.visit_mut_with(self);
+                }
+                member => member.visit_mut_with(self),
+            }
+            (self.in_function, self.in_var_scope) = old;
+        }
+    }
+    fn visit_mut_getter_prop(&mut self, n: &mut GetterProp) {
+        n.key.visit_mut_with(self);
+        let old = (self.in_function, self.in_var_scope);
+        (self.in_function, self.in_var_scope) = (true, true);
+        n.body.visit_mut_with(self);
+        (self.in_function, self.in_var_scope) = old;
+    }
+    fn visit_mut_setter_prop(&mut self, n: &mut SetterProp) {
+        n.key.visit_mut_with(self);
+        let old = (self.in_function, self.in_var_scope);
+        (self.in_function, self.in_var_scope) = (true, true);
+        n.param.visit_mut_with(self);
+        n.body.visit_mut_with(self);
+        (self.in_function, self.in_var_scope) = old;
+    }
+    fn visit_mut_arrow_expr(&mut self, n: &mut ArrowExpr) {
+        let old = replace(&mut self.in_var_scope, true);
+        n.visit_mut_children_with(self);
+        self.in_var_scope = old;
+    }
+    fn visit_mut_stmt(&mut self, n: &mut Stmt) {
+        n.visit_mut_children_with(self);
+        match n {
+            Stmt::Decl(Decl::Var(var)) if var.kind == VarDeclKind::Var && !self.in_var_scope => {
+                *n = match self.var_to_assign(var) {
+                    Some(expr) => expr.into_stmt(),
+                    None => Stmt::Empty(EmptyStmt { span: var.span }),
+                };
+            }
+            _ => {}
+        }
+    }
+    fn visit_mut_for_head(&mut self, n: &mut ForHead) {
+        n.visit_mut_children_with(self);
+        if let ForHead::VarDecl(var) = n {
+            if var.kind == VarDeclKind::Var && var.decls.len() == 1 && !self.in_var_scope {
+                *n = ForHead::Pat(Box::new(var.decls.take().remove(0).name));
+            }
+        }
+    }
+    fn visit_mut_for_stmt(&mut self, n: &mut ForStmt) {
+        n.visit_mut_children_with(self);
+        if let Some(VarDeclOrExpr::VarDecl(var)) = &mut n.init {
+            if var.kind == VarDeclKind::Var && !self.in_var_scope {
+                n.init = self.var_to_assign(var).map(VarDeclOrExpr::Expr);
+            }
+        }
+    }
+}
+
+/// Whether the directive prologue of `stmts` has `"use strict"`.
+fn has_use_strict(stmts: &[Stmt]) -> bool {
+    stmts
+        .iter()
+        .map_while(|stmt| match stmt {
+            Stmt::Expr(ExprStmt { expr, .. }) => match &**expr {
+                Expr::Lit(Lit::Str(str)) => Some(str),
+                _ => None,
+            },
+            _ => None,
+        })
+        .any(|str| {
+            str.raw
+                .as_deref()
+                .is_some_and(|raw| &raw[1..raw.len() - 1] == "use strict")
+        })
+}
@@ -0,0 +1,1270 @@
+use std::collections::{HashMap, HashSet};
+use std::mem::{replace, take};
+
+use swc_core::common::{util::take::Take, Mark, Span, SyntaxContext, DUMMY_SP};
+use swc_core::ecma::ast::*;
+use swc_core::ecma::atoms::JsWord;
+use swc_core::ecma::utils::{find_pat_ids, private_ident, quote_ident, ExprFactory};
+use swc_core::ecma::visit::{
+    noop_visit_mut_type, noop_visit_type, Visit, VisitMut, VisitMutWith, VisitWith,
+};
+
+use super::{has_use_strict, with};
+use crate::utils::{emit_error, emit_warning, key_value};
+
+/// Words that are identifiers in sloppy mode code only.
+const STRICT_RESERVED_WORDS: [&str; 9] = [
+    "implements",
+    "interface",
+    "let",
+    "package",
+    "private",
+    "protected",
+    "public",
+    "static",
+    "yield",
+];
+
+/// Names that strict mode code can not declare.
+fn is_strict_invalid(sym: &str) -> bool {
+    STRICT_RESERVED_WORDS.contains(&sym) || matches!(sym, "eval" | "arguments")
+}
+
+/// `010`, `08`
+fn is_legacy_number(raw: &str) -> bool {
+    let mut chars = raw.chars();
+    chars.next() == Some('0') && chars.next().is_some_and(|c| c.is_ascii_digit())
+}
+
+/// `"\01"`, `"\8"`
+fn has_legacy_escape(raw: &str) -> bool {
+    let mut chars = raw.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            continue;
+        }
+        match chars.next() {
+            Some('1'..='9') => return true,
+            Some('0') if chars.peek().is_some_and(|c| c.is_ascii_digit()) => return true,
+            _ => {}
+        }
+    }
+    false
+}
+
+/// Rewrite the sloppy mode code of a script into strict mode code with the same meaning, see
+/// [`SloppyMode`].
+///
+/// Returns the names that top-level functions use for `arguments.callee`, the name of the
+/// function expression that initializes the global.
+pub(super) fn sloppy_mode(stmts: &mut Vec<Stmt>, unresolved: SyntaxContext) -> HashMap<Id, Ident> {
+    let mut assigned = Assigned::default();
+    stmts.visit_with(&mut assigned);
+    let lexical = lexical_names(stmts, false);
+    let mut sloppy = SloppyMode {
+        unresolved,
+        function: None,
+        depth: 0,
+        in_params: false,
+        nested: false,
+        assigning: false,
+        global_var: false,
+        top_level_lexical: !lexical.is_empty(),
+        scope: VarScope {
+            conflicts: lexical,
+            ..Default::default()
+        },
+        blocks: vec![],
+        assigned: assigned.assigned,
+        renamed: HashMap::new(),
+        callees: HashMap::new(),
+        has_binding: None,
+    };
+    stmts.visit_mut_with(&mut sloppy);
+
+    let mut rename = Rename {
+        ids: sloppy.renamed,
+        blocks: vec![],
+    };
+    let mut prologue = vec![];
+    // The block-level functions of the script are also globals, which the block assigns
+    // when it evaluates the declaration.
+    for (ident, block) in sloppy.scope.block_functions {
+        prologue.push(var(
+            VarDeclKind::Var,
+            Ident::new(ident.sym.clone(), DUMMY_SP.with_ctxt(ident.span.ctxt)),
+            None,
+        ));
+        let local = Ident::new(
+            ident.sym.clone(),
+            DUMMY_SP.with_ctxt(SyntaxContext::empty().apply_mark(Mark::new())),
+        );
+        rename.blocks.push((ident.to_id(), block, local));
+    }
+    if let Some(has_binding) = sloppy.has_binding {
+        prologue.push(with::has_binding(has_binding, unresolved));
+    }
+    stmts.visit_mut_with(&mut rename);
+    stmts.splice(0..0, prologue);
+    sloppy.callees
+}
+
+/// Keep the meaning of sloppy mode code in the strict mode record.
+///
+/// - Legacy octal literals and escapes are printed in decimal.
+/// - `this` of functions is `globalThis` when the function is called without a receiver and is
+///   boxed otherwise (`delete x` is rewritten by [`GlobalScope`](super::GlobalScope)).
+/// - Local bindings named with strict mode reserved words, `eval` or `arguments` are renamed,
+///   functions keep their `name`. Labels named with reserved words are renamed too.
+/// - The earlier of duplicate parameters are renamed, the last one is the binding.
+/// - Function declarations in blocks are also `var` bindings of the function or of the script,
+///   assigned when the block evaluates the declaration (Annex B.3.3).
+/// - `arguments` of functions with simple parameters is linked to the parameters with accessors,
+///   when the function assigns a parameter or writes `arguments`.
+/// - `arguments.callee` is the name of the function.
+/// - Direct `eval` at the top level of the script is indirect `eval`, which evaluates the code in
+///   the global scope too. In functions, it is compiled like in modules.
+/// - Assigning `undefined`, `NaN` or `Infinity` does nothing.
+/// - `with` statements look up the identifiers of their body in the object first.
+///
+/// Class bodies and functions with a `"use strict"` directive are strict mode code already.
+struct SloppyMode {
+    unresolved: SyntaxContext,
+    /// The innermost non-arrow function, `None` at the top level of the script.
+    function: Option<SloppyFunction>,
+    /// The number of functions and arrow functions around, 0 at the top level of the script.
+    depth: usize,
+    in_params: bool,
+    /// Inside a block or a statement, where function declarations are not hoisted in strict mode.
+    nested: bool,
+    /// Visiting the target of an assignment.
+    assigning: bool,
+    /// Visiting the names of a top-level `var`, which are globals.
+    global_var: bool,
+    /// The script has top-level `let`, `const` or `class` declarations.
+    top_level_lexical: bool,
+    /// The innermost function, arrow function or script.
+    scope: VarScope,
+    /// The names declared in each block around, inside of the innermost var scope.
+    blocks: Vec<HashSet<JsWord>>,
+    /// The bindings that are assigned after their declaration.
+    assigned: HashSet<Id>,
+    /// The local bindings that strict mode code can not declare, and their new names.
+    renamed: HashMap<Id, Ident>,
+    /// The top-level functions that use `arguments.callee`, and the name they use for it.
+    callees: HashMap<Id, Ident>,
+    /// The helper of `with` statements, declared at the start of the script when used.
+    has_binding: Option<Ident>,
+}
+
+#[derive(Default)]
+struct SloppyFunction {
+    /// The boxed `this` of the function, declared at the start of the body when it is used.
+    this: Option<Ident>,
+    /// The parameters, empty when they are not linked to `arguments`.
+    params: Vec<Ident>,
+    uses_arguments: bool,
+    assigns_params: bool,
+    writes_arguments: bool,
+    /// Replaces `arguments` when the function assigns it.
+    arguments: Option<Ident>,
+    /// The name of the function that `arguments.callee` is replaced with.
+    callee: Option<Ident>,
+    uses_callee: bool,
+}
+
+#[derive(Default)]
+struct VarScope {
+    /// The parameters and the top-level lexical declarations, block-level functions with these
+    /// names are not `var` bindings.
+    conflicts: HashSet<JsWord>,
+    /// The block-level functions of the script that are globals, with their block.
+    block_functions: Vec<(Ident, Span)>,
+    /// The block-level functions of a function that are `var` bindings, with the function that
+    /// assigns the `var` binding from the block.
+    setters: Vec<(Ident, Ident)>,
+}
+impl VarScope {
+    /// var f, setF = (value) => f = value
+    fn declare_setters(self) -> Vec<Stmt> {
+        self.setters
+            .into_iter()
+            .map(|(function, setter)| {
+                let value = private_ident!("value");
+                let binding = Ident::new(function.sym, DUMMY_SP.with_ctxt(function.span.ctxt));
+                let set = arrow(
+                    vec![value.clone().into()],
+                    Expr::Ident(value).make_assign_to(op!("="), binding.clone().into()),
+                );
+                Stmt::Decl(Decl::Var(Box::new(VarDecl {
+                    span: DUMMY_SP,
+                    kind: VarDeclKind::Var,
+                    declare: false,
+                    decls: vec![declarator(binding, None), declarator(setter, Some(set))],
+                })))
+            })
+            .collect()
+    }
+}
+
+fn declarator(name: Ident, init: Option<Expr>) -> VarDeclarator {
+    VarDeclarator {
+        span: DUMMY_SP,
+        name: name.into(),
+        init: init.map(Box::new),
+        definite: false,
+    }
+}
+
+fn var(kind: VarDeclKind, name: Ident, init: Option<Expr>) -> Stmt {
+    Stmt::Decl(Decl::Var(Box::new(VarDecl {
+        span: DUMMY_SP,
+        kind,
+        declare: false,
+        decls: vec![declarator(name, init)],
+    })))
+}
+
+fn arrow(params: Vec<Pat>, body: Expr) -> Expr {
+    Expr::Arrow(ArrowExpr {
+        span: DUMMY_SP,
+        params,
+        body: Box::new(BlockStmtOrExpr::Expr(Box::new(body))),
+        is_async: false,
+        is_generator: false,
+        type_params: None,
+        return_type: None,
+    })
+}
+
+/// The names declared by `let`, `const` and `class` (and function declarations with
+/// `functions`) directly in `stmts`.
+fn lexical_names(stmts: &[Stmt], functions: bool) -> HashSet<JsWord> {
+    let mut names = HashSet::new();
+    for stmt in stmts {
+        match stmt {
+            Stmt::Decl(Decl::Var(var)) if var.kind != VarDeclKind::Var => names.extend(
+                find_pat_ids::<_, Id>(&var.decls)
+                    .into_iter()
+                    .map(|(sym, _)| sym),
+            ),
+            Stmt::Decl(Decl::Class(class)) => {
+                names.insert(class.ident.sym.clone());
+            }
+            Stmt::Decl(Decl::Fn(f)) if functions => {
+                names.insert(f.ident.sym.clone());
+            }
+            _ => {}
+        }
+    }
+    names
+}
+
+impl SloppyMode {
+    fn global(&self, sym: &str) -> Ident {
+        Ident::new(sym.into(), DUMMY_SP.with_ctxt(self.unresolved))
+    }
+    /// A declared binding.
+    fn declare(&mut self, ident: &Ident) {
+        if self.global_var || ident.span.ctxt == self.unresolved || !is_strict_invalid(&ident.sym) {
+            return;
+        }
+        self.renamed
+            .entry(ident.to_id())
+            .or_insert_with(|| private_ident!(format!("_{}", ident.sym)));
+    }
+    /// An assignment target.
+    fn target(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Ident(ident) => {
+                if let Some(function) = &mut self.function {
+                    if function
+                        .params
+                        .iter()
+                        .any(|param| param.to_id() == ident.to_id())
+                    {
+                        function.assigns_params = true;
+                    }
+                }
+            }
+            Expr::Paren(ParenExpr { expr, .. }) => self.target(expr),
+            Expr::Member(MemberExpr { obj, .. }) if obj.is_ident_ref_to("arguments") => {
+                if let Some(function) = &mut self.function {
+                    function.writes_arguments = true;
+                }
+            }
+            _ => {}
+        }
+    }
+    /// `undefined`, `NaN` and `Infinity` are not writable.
+    fn is_read_only(&self, ident: &Ident) -> bool {
+        ident.span.ctxt == self.unresolved
+            && matches!(&*ident.sym, "undefined" | "NaN" | "Infinity")
+    }
+    fn read_only_target(&self, target: &PatOrExpr) -> Option<Ident> {
+        let ident = match target {
+            PatOrExpr::Expr(expr) => expr.as_ident(),
+            PatOrExpr::Pat(pat) => match &**pat {
+                Pat::Ident(ident) => Some(&ident.id),
+                Pat::Expr(expr) => expr.as_ident(),
+                _ => None,
+            },
+        };
+        ident.filter(|ident| self.is_read_only(ident)).cloned()
+    }
+    /// this == null ? globalThis : Object(this)
+    fn boxed_this(&self) -> Expr {
+        Expr::Cond(CondExpr {
+            span: DUMMY_SP,
+            test: Box::new(Expr::Bin(BinExpr {
+                span: DUMMY_SP,
+                op: op!("=="),
+                left: Box::new(Expr::This(ThisExpr { span: DUMMY_SP })),
+                right: Box::new(Lit::Null(Null { span: DUMMY_SP }).into()),
+            })),
+            cons: Box::new(self.global("globalThis").into()),
+            alt: Box::new(
+                self.global("Object")
+                    .as_call(DUMMY_SP, vec![ThisExpr { span: DUMMY_SP }.as_arg()]),
+            ),
+        })
+    }
+    /// i < arguments.length && Object.defineProperty(arguments, i, {
+    ///     get: () => a, set: (value) => a = value, enumerable: true, configurable: true
+    /// })
+    fn link_argument(&self, index: usize, param: &Ident) -> Stmt {
+        let value = private_ident!("value");
+        let descriptor = ObjectLit {
+            span: DUMMY_SP,
+            props: vec![
+                key_value("get".into(), arrow(vec![], param.clone().into())),
+                key_value(
+                    "set".into(),
+                    arrow(
+                        vec![value.clone().into()],
+                        Expr::Ident(value).make_assign_to(op!("="), param.clone().into()),
+                    ),
+                ),
+                key_value("enumerable".into(), true.into()),
+                key_value("configurable".into(), true.into()),
+            ],
+        };
+        let define = self
+            .global("Object")
+            .make_member(quote_ident!("defineProperty"))
+            .as_call(
+                DUMMY_SP,
+                vec![
+                    self.global("arguments").as_arg(),
+                    Expr::from(index as f64).as_arg(),
+                    descriptor.as_arg(),
+                ],
+            );
+        Expr::Bin(BinExpr {
+            span: DUMMY_SP,
+            op: op!("&&"),
+            left: Box::new(Expr::Bin(BinExpr {
+                span: DUMMY_SP,
+                op: op!("<"),
+                left: Box::new(Expr::from(index as f64)),
+                right: Box::new(self.global("arguments").make_member(quote_ident!("length"))),
+            })),
+            right: Box::new(define),
+        })
+        .into_stmt()
+    }
+    /// A non-arrow function, getter or setter. Returns whether it uses `callee` for
+    /// `arguments.callee`.
+    fn function(
+        &mut self,
+        mut params: Vec<&mut Pat>,
+        body: &mut Option<BlockStmt>,
+        callee: Option<Ident>,
+    ) -> bool {
+        let Some(body) = body else { return false };
+        if has_use_strict(&body.stmts) {
+            return false;
+        }
+        // The last of duplicate parameters is the binding.
+        let mut names = HashSet::new();
+        for param in params.iter_mut().rev() {
+            if let Pat::Ident(ident) = &mut **param {
+                if !names.insert(ident.sym.clone()) {
+                    ident.id = private_ident!(format!("_{}", ident.sym));
+                }
+            }
+        }
+        let linked = match params.iter().all(|param| param.is_ident()) {
+            true => params
+                .iter()
+                .map(|param| param.as_ident().unwrap().id.clone())
+                .collect(),
+            false => vec![],
+        };
+        let mut scan = ArgumentsScan {
+            unresolved: self.unresolved,
+            ..Default::default()
+        };
+        for param in &params {
+            param.visit_with(&mut scan);
+        }
+        body.stmts.visit_with(&mut scan);
+        // `arguments` is assigned, it starts as the arguments object.
+        let mut arguments = None;
+        if !scan.declared {
+            if let Some(id) = scan.var {
+                let alias = private_ident!("_arguments");
+                self.renamed.insert(id, alias.clone());
+                arguments = Some((alias, false));
+            } else if scan.assigned {
+                arguments = Some((private_ident!("_arguments"), true));
+            }
+        }
+        let mut conflicts = lexical_names(&body.stmts, false);
+        for param in &params {
+            conflicts.extend(
+                find_pat_ids::<_, Id>(&**param)
+                    .into_iter()
+                    .map(|(sym, _)| sym),
+            );
+        }
+
+        let outer = self.function.replace(SloppyFunction {
+            params: linked,
+            arguments: arguments
+                .as_ref()
+                .filter(|(_, unresolved)| *unresolved)
+                .map(|(alias, _)| alias.clone()),
+            callee,
+            ..Default::default()
+        });
+        let scope = replace(
+            &mut self.scope,
+            VarScope {
+                conflicts,
+                ..Default::default()
+            },
+        );
+        let blocks = take(&mut self.blocks);
+        let state = (self.in_params, self.nested, self.assigning, self.global_var);
+        (self.assigning, self.global_var) = (false, false);
+        self.depth += 1;
+        self.in_params = true;
+        for param in &mut params {
+            param.visit_mut_with(self);
+        }
+        self.in_params = false;
+        self.nested = false;
+        body.stmts.visit_mut_with(self);
+        self.depth -= 1;
+        (self.in_params, self.nested, self.assigning, self.global_var) = state;
+        self.blocks = blocks;
+        let scope = replace(&mut self.scope, scope);
+        let function = replace(&mut self.function, outer).unwrap();
+
+        let mut prologue = vec![];
+        if let Some(this) = function.this {
+            prologue.push(var(VarDeclKind::Var, this, Some(self.boxed_this())));
+        }
+        if let Some((alias, _)) = arguments {
+            prologue.push(var(
+                VarDeclKind::Var,
+                alias,
+                Some(self.global("arguments").into()),
+            ));
+        }
+        if function.uses_arguments && (function.assigns_params || function.writes_arguments) {
+            for (index, param) in function.params.iter().enumerate() {
+                prologue.push(self.link_argument(index, param));
+            }
+        }
+        prologue.extend(scope.declare_setters());
+        body.stmts.splice(0..0, prologue);
+        function.uses_callee
+    }
+    /// The function declarations directly in a block are also `var` bindings, unless the
+    /// `var` binding would conflict with a lexical declaration.
+    fn hoist_block_functions(&mut self, stmts: &mut Vec<Stmt>, block: Span) {
+        for index in (0..stmts.len()).rev() {
+            let Stmt::Decl(Decl::Fn(f)) = &stmts[index] else {
+                continue;
+            };
+            let ident = &f.ident;
+            if f.function.is_async
+                || f.function.is_generator
+                || is_strict_invalid(&ident.sym)
+                || self.scope.conflicts.contains(&ident.sym)
+                || self.blocks.iter().any(|names| names.contains(&ident.sym))
+            {
+                continue;
+            }
+            let copy = if self.depth == 0 {
+                // f = f, the global from the local of the block
+                self.scope.block_functions.push((ident.clone(), block));
+                Expr::Ident(ident.clone()).make_assign_to(
+                    op!("="),
+                    Ident::new(ident.sym.clone(), DUMMY_SP.with_ctxt(ident.span.ctxt)).into(),
+                )
+            } else {
+                // The binding of the block and the `var` binding have the same id, the setter
+                // is declared outside of the block.
+                let setters = &mut self.scope.setters;
+                let setter = match setters.iter().find(|(f, _)| f.to_id() == ident.to_id()) {
+                    Some((_, setter)) => setter.clone(),
+                    None => {
+                        let setter = private_ident!(format!("set_{}", ident.sym));
+                        setters.push((ident.clone(), setter.clone()));
+                        setter
+                    }
+                };
+                setter.as_call(DUMMY_SP, vec![ident.clone().as_arg()])
+            };
+            stmts.insert(index + 1, copy.into_stmt());
+        }
+    }
+    fn block(&mut self, stmts: &mut Vec<Stmt>, span: Span) {
+        self.blocks.push(lexical_names(stmts, true));
+        stmts.visit_mut_with(self);
+        self.blocks.pop();
+        self.hoist_block_functions(stmts, span);
+    }
+    /// `arguments.callee` in a non-arrow function.
+    fn is_callee(&self, member: &MemberExpr) -> bool {
+        self.function.is_some()
+            && matches!(&*member.obj, Expr::Ident(obj) if &*obj.sym == "arguments" && obj.span.ctxt == self.unresolved)
+            && matches!(&member.prop, MemberProp::Ident(prop) if &*prop.sym == "callee")
+    }
+}
+
+impl VisitMut for SloppyMode {
+    noop_visit_mut_type!();
+
+    /// Class bodies are strict mode code, the heritage and computed keys are not.
+    fn visit_mut_class(&mut self, n: &mut Class) {
+        n.decorators.visit_mut_with(self);
+        n.super_class.visit_mut_with(self);
+        for member in &mut n.body {
+            match member {
+                ClassMember::Method(method) => method.key.visit_mut_with(self),
+                ClassMember::ClassProp(prop) => prop.key.visit_mut_with(self),
+                ClassMember::AutoAccessor(AutoAccessor {
+                    key: Key::Public(key),
+                    ..
+                }) => key.visit_mut_with(self),
+                _ => {}
+            }
+        }
+    }
+    fn visit_mut_function(&mut self, n: &mut Function) {
+        let params = n.params.iter_mut().map(|param| &mut param.pat).collect();
+        self.function(params, &mut n.body, None);
+    }
+    fn visit_mut_getter_prop(&mut self, n: &mut GetterProp) {
+        n.key.visit_mut_with(self);
+        self.function(vec![], &mut n.body, None);
+    }
+    fn visit_mut_setter_prop(&mut self, n: &mut SetterProp) {
+        n.key.visit_mut_with(self);
+        self.function(vec![&mut n.param], &mut n.body, None);
+    }
+    fn visit_mut_fn_decl(&mut self, n: &mut FnDecl) {
+        let top_level = self.depth == 0 && !self.nested;
+        let callee = if top_level {
+            // A top-level function is a global that the script may assign, the function
+            // expression that initializes it has its own name.
+            Some(Ident::new(
+                n.ident.sym.clone(),
+                DUMMY_SP.with_ctxt(SyntaxContext::empty().apply_mark(Mark::new())),
+            ))
+            .filter(|_| !is_strict_invalid(&n.ident.sym))
+        } else {
+            self.declare(&n.ident);
+            Some(n.ident.clone()).filter(|ident| !self.assigned.contains(&ident.to_id()))
+        };
+        let callee = callee.filter(|callee| !declares(&n.function, &callee.sym));
+        let params = n
+            .function
+            .params
+            .iter_mut()
+            .map(|param| &mut param.pat)
+            .collect();
+        if self.function(params, &mut n.function.body, callee.clone()) && top_level {
+            self.callees.insert(n.ident.to_id(), callee.unwrap());
+        }
+    }
+    fn visit_mut_fn_expr(&mut self, n: &mut FnExpr) {
+        if let Some(ident) = &n.ident {
+            self.declare(ident);
+        }
+        let callee = n
+            .ident
+            .clone()
+            .filter(|callee| !declares(&n.function, &callee.sym));
+        let params = n
+            .function
+            .params
+            .iter_mut()
+            .map(|param| &mut param.pat)
+            .collect();
+        self.function(params, &mut n.function.body, callee);
+    }
+    fn visit_mut_arrow_expr(&mut self, n: &mut ArrowExpr) {
+        let mut conflicts: HashSet<JsWord> = find_pat_ids::<_, Id>(&n.params)
+            .into_iter()
+            .map(|(sym, _)| sym)
+            .collect();
+        if let BlockStmtOrExpr::BlockStmt(body) = &*n.body {
+            conflicts.extend(lexical_names(&body.stmts, false));
+        }
+        let scope = replace(
+            &mut self.scope,
+            VarScope {
+                conflicts,
+                ..Default::default()
+            },
+        );
+        let blocks = take(&mut self.blocks);
+        let state = (self.nested, self.assigning, self.global_var);
+        (self.assigning, self.global_var) = (false, false);
+        self.depth += 1;
+        n.params.visit_mut_with(self);
+        self.nested = false;
+        match &mut *n.body {
+            BlockStmtOrExpr::BlockStmt(body) => body.stmts.visit_mut_with(self),
+            BlockStmtOrExpr::Expr(expr) => expr.visit_mut_with(self),
+        }
+        self.depth -= 1;
+        (self.nested, self.assigning, self.global_var) = state;
+        self.blocks = blocks;
+        let scope = replace(&mut self.scope, scope);
+        if let BlockStmtOrExpr::BlockStmt(body) = &mut *n.body {
+            body.stmts.splice(0..0, scope.declare_setters());
+        }
+    }
+    fn visit_mut_stmt(&mut self, n: &mut Stmt) {
+        // l: function f() {}
+        if let Stmt::Labeled(LabeledStmt { body, .. }) = n {
+            if matches!(&**body, Stmt::Decl(Decl::Fn(_))) {
+                *n = *body.take();
+            }
+        }
+        // The function declarations directly in a script or a function body are hoisted.
+        let nested = self.nested;
+        self.nested = nested || !matches!(n, Stmt::Decl(_));
+        n.visit_mut_children_with(self);
+        self.nested = nested;
+        if let Stmt::With(with) = n {
+            let with = replace(
+                with,
+                WithStmt {
+                    span: DUMMY_SP,
+                    obj: Expr::dummy().into(),
+                    body: Stmt::dummy().into(),
+                },
+            );
+            let has_binding = self
+                .has_binding
+                .get_or_insert_with(|| private_ident!("hasBinding"))
+                .clone();
+            *n = with::rewrite_with(with, &has_binding, self.unresolved);
+        }
+    }
+    fn visit_mut_block_stmt(&mut self, n: &mut BlockStmt) {
+        self.block(&mut n.stmts, n.span);
+    }
+    fn visit_mut_switch_stmt(&mut self, n: &mut SwitchStmt) {
+        n.discriminant.visit_mut_with(self);
+        self.blocks.push(
+            n.cases
+                .iter()
+                .flat_map(|case| lexical_names(&case.cons, true))
+                .collect(),
+        );
+        n.cases.visit_mut_with(self);
+        self.blocks.pop();
+        for case in &mut n.cases {
+            self.hoist_block_functions(&mut case.cons, n.span);
+        }
+    }
+    fn visit_mut_labeled_stmt(&mut self, n: &mut LabeledStmt) {
+        rename_label(&mut n.label);
+        n.body.visit_mut_with(self);
+    }
+    fn visit_mut_break_stmt(&mut self, n: &mut BreakStmt) {
+        if let Some(label) = &mut n.label {
+            rename_label(label);
+        }
+    }
+    fn visit_mut_continue_stmt(&mut self, n: &mut ContinueStmt) {
+        if let Some(label) = &mut n.label {
+            rename_label(label);
+        }
+    }
+    fn visit_mut_var_decl(&mut self, n: &mut VarDecl) {
+        let global = n.kind == VarDeclKind::Var && self.depth == 0;
+        for decl in &mut n.decls {
+            let old = replace(&mut self.global_var, global);
+            decl.name.visit_mut_with(self);
+            self.global_var = old;
+            decl.init.visit_mut_with(self);
+        }
+    }
+    fn visit_mut_binding_ident(&mut self, n: &mut BindingIdent) {
+        if self.assigning {
+            self.target(&Expr::Ident(n.id.clone()));
+        } else {
+            self.declare(&n.id);
+        }
+        n.visit_mut_children_with(self);
+    }
+    fn visit_mut_assign_pat_prop(&mut self, n: &mut AssignPatProp) {
+        if self.assigning {
+            self.target(&Expr::Ident(n.key.clone()));
+        } else {
+            self.declare(&n.key);
+        }
+        n.visit_mut_children_with(self);
+    }
+    fn visit_mut_pat(&mut self, n: &mut Pat) {
+        if self.assigning {
+            let ident = match n {
+                Pat::Ident(ident) => Some(&ident.id),
+                Pat::Expr(expr) => expr.as_ident(),
+                _ => None,
+            };
+            // [undefined] = [1]
+            if ident.is_some_and(|ident| self.is_read_only(ident)) {
+                let ignored = ObjectLit {
+                    span: DUMMY_SP,
+                    props: vec![],
+                };
+                *n = Pat::Expr(Box::new(Expr::from(ignored).make_member(quote_ident!("_"))));
+                return;
+            }
+            if let Pat::Expr(expr) = n {
+                self.target(expr);
+            }
+        }
+        n.visit_mut_children_with(self);
+    }
+    fn visit_mut_assign_expr(&mut self, n: &mut AssignExpr) {
+        if let PatOrExpr::Expr(expr) = &n.left {
+            self.target(expr);
+        }
+        let assigning = replace(&mut self.assigning, true);
+        n.left.visit_mut_with(self);
+        self.assigning = assigning;
+        n.right.visit_mut_with(self);
+    }
+    fn visit_mut_for_head(&mut self, n: &mut ForHead) {
+        let assigning = replace(&mut self.assigning, matches!(n, ForHead::Pat(_)));
+        n.visit_mut_children_with(self);
+        self.assigning = assigning;
+    }
+    fn visit_mut_update_expr(&mut self, n: &mut UpdateExpr) {
+        self.target(&n.arg);
+        n.visit_mut_children_with(self);
+    }
+    fn visit_mut_member_prop(&mut self, n: &mut MemberProp) {
+        if let MemberProp::Computed(computed) = n {
+            computed.visit_mut_with(self);
+        }
+    }
+    fn visit_mut_super_prop(&mut self, n: &mut SuperProp) {
+        if let SuperProp::Computed(computed) = n {
+            computed.visit_mut_with(self);
+        }
+    }
+    fn visit_mut_prop_name(&mut self, n: &mut PropName) {
+        match n {
+            PropName::Computed(computed) => computed.visit_mut_with(self),
+            PropName::Num(num) => num.visit_mut_with(self),
+            PropName::Str(str) => str.visit_mut_with(self),
+            _ => {}
+        }
+    }
+    fn visit_mut_prop(&mut self, n: &mut Prop) {
+        // { arguments } keeps its key when `arguments` is renamed.
+        if let Prop::Shorthand(ident) = n {
+            if &*ident.sym == "arguments" {
+                *n = Prop::KeyValue(KeyValueProp {
+                    key: PropName::Ident(quote_ident!(ident.span, "arguments")),
+                    value: Box::new(ident.take().into()),
+                });
+            }
+        }
+        n.visit_mut_children_with(self);
+    }
+    fn visit_mut_call_expr(&mut self, n: &mut CallExpr) {
+        if let Callee::Expr(callee) = &mut n.callee {
+            if let Expr::Ident(ident) = &**callee {
+                if &*ident.sym == "eval" && ident.span.ctxt == self.unresolved && self.depth == 0 {
+                    if self.top_level_lexical {
+                        emit_warning(
+                            n.span,
+                            "Direct `eval` at the top level of a script is compiled to an \
+                             indirect eval, the evaluated code can not see the top-level `let`, \
+                             `const` and `class` declarations of the script.",
+                        );
+                    }
+                    // (0, eval)(code) evaluates the code in the global scope like the direct eval
+                    // of the top level.
+                    let eval = callee.take();
+                    **callee = Expr::Paren(ParenExpr {
+                        span: DUMMY_SP,
+                        expr: Box::new(Expr::Seq(SeqExpr {
+                            span: DUMMY_SP,
+                            exprs: vec![0.0.into(), eval],
+                        })),
+                    });
+                }
+            }
+        }
+        n.visit_mut_children_with(self);
+    }
+    fn visit_mut_ident(&mut self, n: &mut Ident) {
+        if &*n.sym == "arguments" && n.span.ctxt == self.unresolved {
+            if let Some(function) = &mut self.function {
+                function.uses_arguments = true;
+                if let Some(alias) = &function.arguments {
+                    *n = Ident::new(alias.sym.clone(), n.span.with_ctxt(alias.span.ctxt));
+                }
+            }
+        }
+    }
+    fn visit_mut_expr(&mut self, n: &mut Expr) {
+        match n {
+            Expr::This(_) => {
+                if let Some(function) = &mut self.function {
+                    *n = match self.in_params {
+                        // The parameters are evaluated before the body declares `_this`.
+                        true => Expr::Paren(ParenExpr {
+                            span: DUMMY_SP,
+                            expr: Box::new(self.boxed_this()),
+                        }),
+                        false => Expr::Ident(
+                            function
+                                .this
+                                .get_or_insert_with(|| private_ident!("_this"))
+                                .clone(),
+                        ),
+                    };
+                }
+            }
+            Expr::Member(member) if self.is_callee(member) => {
+                let function = self.function.as_mut().unwrap();
+                match &function.callee {
+                    Some(callee) => {
+                        function.uses_callee = true;
+                        *n = Expr::Ident(callee.clone());
+                    }
+                    None => emit_error(
+                        member.span,
+                        "`arguments.callee` of an anonymous or reassigned function is not \
+                         supported, records are strict mode code.",
+                    ),
+                }
+            }
+            // undefined = 1 does nothing
+            Expr::Assign(assign) if self.read_only_target(&assign.left).is_some() => {
+                let ident = self.read_only_target(&assign.left).unwrap();
+                assign.right.visit_mut_with(self);
+                *n = match assign.op.to_update() {
+                    Some(op) => Expr::Bin(BinExpr {
+                        span: assign.span,
+                        op,
+                        left: Box::new(ident.into()),
+                        right: assign.right.take(),
+                    }),
+                    None => *assign.right.take(),
+                };
+            }
+            // undefined++ is +undefined, ++undefined is undefined + 1
+            Expr::Update(update)
+                if update
+                    .arg
+                    .as_ident()
+                    .is_some_and(|arg| self.is_read_only(arg)) =>
+            {
+                let arg = update.arg.take();
+                *n = match update.prefix {
+                    true => Expr::Bin(BinExpr {
+                        span: update.span,
+                        op: match update.op {
+                            op!("++") => op!(bin, "+"),
+                            op!("--") => op!(bin, "-"),
+                        },
+                        left: arg,
+                        right: Box::new(1.0.into()),
+                    }),
+                    false => Expr::Unary(UnaryExpr {
+                        span: update.span,
+                        op: op!(unary, "+"),
+                        arg,
+                    }),
+                };
+            }
+            _ => n.visit_mut_children_with(self),
+        }
+    }
+    fn visit_mut_number(&mut self, n: &mut Number) {
+        if n.raw.as_deref().is_some_and(is_legacy_number) {
+            n.raw = None;
+        }
+    }
+    fn visit_mut_str(&mut self, n: &mut Str) {
+        if n.raw.as_deref().is_some_and(has_legacy_escape) {
+            n.raw = None;
+        }
+    }
+}
+
+fn rename_label(label: &mut Ident) {
+    if STRICT_RESERVED_WORDS.contains(&&*label.sym) {
+        label.sym = format!("_{}", label.sym).into();
+    }
+}
+
+/// Whether the parameters or the body of `function` declare `sym`, outside of nested
+/// non-arrow functions.
+fn declares(function: &Function, sym: &JsWord) -> bool {
+    struct Declares<'a> {
+        sym: &'a JsWord,
+        found: bool,
+    }
+    impl Visit for Declares<'_> {
+        noop_visit_type!();
+        fn visit_binding_ident(&mut self, n: &BindingIdent) {
+            self.found |= n.id.sym == *self.sym;
+        }
+        fn visit_fn_decl(&mut self, n: &FnDecl) {
+            self.found |= n.ident.sym == *self.sym;
+        }
+        fn visit_class_decl(&mut self, n: &ClassDecl) {
+            self.found |= n.ident.sym == *self.sym;
+        }
+        fn visit_function(&mut self, _: &Function) {}
+        fn visit_class(&mut self, _: &Class) {}
+    }
+    let mut declares = Declares { sym, found: false };
+    function.params.visit_with(&mut declares);
+    function.body.visit_with(&mut declares);
+    declares.found
+}
+
+/// How a function declares or assigns `arguments`.
+#[derive(Default)]
+struct ArgumentsScan {
+    unresolved: SyntaxContext,
+    /// A parameter or a function declaration named `arguments`.
+    declared: bool,
+    /// `var arguments` of the function.
+    var: Option<Id>,
+    /// The function or its arrow functions assign `arguments` without declaring it.
+    assigned: bool,
+    in_arrow: bool,
+}
+impl ArgumentsScan {
+    fn assigns(&mut self, ids: Vec<Id>) {
+        self.assigned |= ids
+            .iter()
+            .any(|(sym, ctxt)| &**sym == "arguments" && *ctxt == self.unresolved);
+    }
+}
+impl Visit for ArgumentsScan {
+    noop_visit_type!();
+    fn visit_pat(&mut self, n: &Pat) {
+        // Only the parameters are visited as patterns outside of declarations.
+        if !self.in_arrow {
+            self.declared |= find_pat_ids::<_, Id>(n)
+                .iter()
+                .any(|(sym, _)| &**sym == "arguments");
+        }
+    }
+    fn visit_stmts(&mut self, n: &[Stmt]) {
+        for stmt in n {
+            if let Stmt::Decl(Decl::Fn(f)) = stmt {
+                self.declared |= !self.in_arrow && &*f.ident.sym == "arguments";
+            }
+            stmt.visit_with(self);
+        }
+    }
+    fn visit_var_decl(&mut self, n: &VarDecl) {
+        if n.kind == VarDeclKind::Var && !self.in_arrow {
+            let ids = find_pat_ids::<_, Id>(&n.decls);
+            if let Some(id) = ids.into_iter().find(|(sym, _)| &**sym == "arguments") {
+                self.var = Some(id);
+            }
+        }
+        for decl in &n.decls {
+            decl.init.visit_with(self);
+        }
+    }
+    fn visit_assign_expr(&mut self, n: &AssignExpr) {
+        match &n.left {
+            PatOrExpr::Expr(expr) => {
+                if let Expr::Ident(ident) = &**expr {
+                    self.assigns(vec![ident.to_id()]);
+                }
+            }
+            PatOrExpr::Pat(pat) => self.assigns(find_pat_ids(&**pat)),
+        }
+        n.right.visit_with(self);
+    }
+    fn visit_update_expr(&mut self, n: &UpdateExpr) {
+        if let Expr::Ident(ident) = &*n.arg {
+            self.assigns(vec![ident.to_id()]);
+        }
+    }
+    fn visit_for_head(&mut self, n: &ForHead) {
+        match n {
+            ForHead::Pat(pat) => self.assigns(find_pat_ids(&**pat)),
+            _ => n.visit_children_with(self),
+        }
+    }
+    fn visit_function(&mut self, _: &Function) {}
+    fn visit_class(&mut self, _: &Class) {}
+    fn visit_arrow_expr(&mut self, n: &ArrowExpr) {
+        let in_arrow = replace(&mut self.in_arrow, true);
+        n.body.visit_with(self);
+        self.in_arrow = in_arrow;
+    }
+}
+
+/// The bindings of a script that are assigned after their declaration.
+#[derive(Default)]
+struct Assigned {
+    assigned: HashSet<Id>,
+    functions: HashSet<Id>,
+}
+impl Visit for Assigned {
+    noop_visit_type!();
+    fn visit_assign_expr(&mut self, n: &AssignExpr) {
+        match &n.left {
+            PatOrExpr::Expr(expr) => {
+                if let Expr::Ident(ident) = &**expr {
+                    self.assigned.insert(ident.to_id());
+                }
+            }
+            PatOrExpr::Pat(pat) => self.assigned.extend(find_pat_ids::<_, Id>(&**pat)),
+        }
+        n.visit_children_with(self);
+    }
+    fn visit_update_expr(&mut self, n: &UpdateExpr) {
+        if let Expr::Ident(ident) = &*n.arg {
+            self.assigned.insert(ident.to_id());
+        }
+        n.visit_children_with(self);
+    }
+    fn visit_for_head(&mut self, n: &ForHead) {
+        if let ForHead::Pat(pat) = n {
+            self.assigned.extend(find_pat_ids::<_, Id>(&**pat));
+        }
+        n.visit_children_with(self);
+    }
+    fn visit_var_declarator(&mut self, n: &VarDeclarator) {
+        if n.init.is_some() {
+            self.assigned.extend(find_pat_ids::<_, Id>(&n.name));
+        }
+        n.visit_children_with(self);
+    }
+    fn visit_fn_decl(&mut self, n: &FnDecl) {
+        // function f() {} function f() {}
+        if !self.functions.insert(n.ident.to_id()) {
+            self.assigned.insert(n.ident.to_id());
+        }
+        n.visit_children_with(self);
+    }
+}
+
+/// Rename the bindings that strict mode code can not declare, and the block-level functions of
+/// the script inside their block.
+struct Rename {
+    ids: HashMap<Id, Ident>,
+    blocks: Vec<(Id, Span, Ident)>,
+}
+impl Rename {
+    fn renamed(&self, ident: &Ident) -> Option<Ident> {
+        let id = ident.to_id();
+        let span = ident.span;
+        let target = self
+            .blocks
+            .iter()
+            .find(|(block_id, block, _)| {
+                *block_id == id && !span.is_dummy() && block.lo <= span.lo && span.hi <= block.hi
+            })
+            .map(|(_, _, target)| target)
+            .or_else(|| self.ids.get(&id))?;
+        Some(Ident::new(
+            target.sym.clone(),
+            span.with_ctxt(target.span.ctxt),
+        ))
+    }
+    /// A renamed function that keeps its `name`.
+    ///
+    /// { static: function () {} }.static
+    fn named_function(name: JsWord, function: Box<Function>) -> Expr {
+        Expr::from(ObjectLit {
+            span: DUMMY_SP,
+            props: vec![key_value(
+                name.clone(),
+                FnExpr {
+                    ident: None,
+                    function,
+                }
+                .into(),
+            )],
+        })
+        .make_member(quote_ident!(name))
+    }
+    /// function static() {}
+    /// =>
+    /// var _static = { static: function () {} }.static
+    ///
+    /// at the start of `stmts`.
+    fn stmts(&mut self, stmts: &mut Vec<Stmt>, kind: VarDeclKind) {
+        let names: Vec<_> = stmts
+            .iter()
+            .map(|stmt| match stmt {
+                Stmt::Decl(Decl::Fn(f)) => self
+                    .renamed(&f.ident)
+                    .filter(|renamed| renamed.sym != f.ident.sym)
+                    .map(|_| f.ident.sym.clone()),
+                _ => None,
+            })
+            .collect();
+        stmts.visit_mut_children_with(self);
+        let mut hoisted = vec![];
+        let mut rest = Vec::with_capacity(stmts.len());
+        for (stmt, name) in stmts.drain(..).zip(names) {
+            match (stmt, name) {
+                (
+                    Stmt::Decl(Decl::Fn(FnDecl {
+                        ident, function, ..
+                    })),
+                    Some(name),
+                ) => hoisted.push(var(kind, ident, Some(Self::named_function(name, function)))),
+                (stmt, _) => rest.push(stmt),
+            }
+        }
+        hoisted.extend(rest);
+        *stmts = hoisted;
+    }
+}
+impl VisitMut for Rename {
+    noop_visit_mut_type!();
+    fn visit_mut_ident(&mut self, n: &mut Ident) {
+        if let Some(renamed) = self.renamed(n) {
+            *n = renamed;
+        }
+    }
+    fn visit_mut_expr(&mut self, n: &mut Expr) {
+        let name = match n {
+            Expr::Fn(FnExpr {
+                ident: Some(ident), ..
+            }) => self
+                .renamed(ident)
+                .filter(|renamed| renamed.sym != ident.sym)
+                .map(|_| ident.sym.clone()),
+            _ => None,
+        };
+        n.visit_mut_children_with(self);
+        // (() => { var _static = { static: function () {} }.static; return _static })()
+        if let Some(name) = name {
+            let Expr::Fn(FnExpr {
+                ident: Some(ident),
+                function,
+            }) = n.take()
+            else {
+                unreachable!()
+            };
+            let body = BlockStmt {
+                span: DUMMY_SP,
+                stmts: vec![
+                    var(
+                        VarDeclKind::Var,
+                        ident.clone(),
+                        Some(Self::named_function(name, function)),
+                    ),
+                    Stmt::Return(ReturnStmt {
+                        span: DUMMY_SP,
+                        arg: Some(Box::new(ident.into())),
+                    }),
+                ],
+            };
+            *n = Expr::Paren(ParenExpr {
+                span: DUMMY_SP,
+                expr: Box::new(Expr::Arrow(ArrowExpr {
+                    body: Box::new(BlockStmtOrExpr::BlockStmt(body)),
+                    ..ArrowExpr::dummy()
+                })),
+            })
+            .as_call(DUMMY_SP, vec![]);
+        }
+    }
+    fn visit_mut_function(&mut self, n: &mut Function) {
+        n.decorators.visit_mut_with(self);
+        n.params.visit_mut_with(self);
+        if let Some(body) = &mut n.body {
+            self.stmts(&mut body.stmts, VarDeclKind::Var);
+        }
+    }
+    fn visit_mut_arrow_expr(&mut self, n: &mut ArrowExpr) {
+        n.params.visit_mut_with(self);
+        match &mut *n.body {
+            BlockStmtOrExpr::BlockStmt(body) => self.stmts(&mut body.stmts, VarDeclKind::Var),
+            BlockStmtOrExpr::Expr(expr) => expr.visit_mut_with(self),
+        }
+    }
+    fn visit_mut_block_stmt(&mut self, n: &mut BlockStmt) {
+        self.stmts(&mut n.stmts, VarDeclKind::Let);
+    }
+    fn visit_mut_prop(&mut self, n: &mut Prop) {
+        if let Prop::Shorthand(ident) = n {
+            if let Some(renamed) = self.renamed(ident) {
+                *n = Prop::KeyValue(KeyValueProp {
+                    key: PropName::Ident(quote_ident!(ident.span, ident.sym.clone())),
+                    value: Box::new(renamed.into()),
+                });
+                return;
+            }
+        }
+        n.visit_mut_children_with(self);
+    }
+    fn visit_mut_object_pat_prop(&mut self, n: &mut ObjectPatProp) {
+        if let ObjectPatProp::Assign(AssignPatProp { span, key, value }) = n {
+            if let Some(renamed) = self.renamed(key) {
+                value.visit_mut_with(self);
+                let target = Box::new(Pat::Ident(renamed.into()));
+                *n = ObjectPatProp::KeyValue(KeyValuePatProp {
+                    key: PropName::Ident(quote_ident!(key.span, key.sym.clone())),
+                    value: match value.take() {
+                        Some(value) => Box::new(Pat::Assign(AssignPat {
+                            span: *span,
+                            left: target,
+                            right: value,
+                        })),
+                        None => target,
+                    },
+                });
+                return;
+            }
+        }
+        n.visit_mut_children_with(self);
+    }
+    fn visit_mut_member_prop(&mut self, n: &mut MemberProp) {
+        if let MemberProp::Computed(computed) = n {
+            computed.visit_mut_with(self);
+        }
+    }
+    fn visit_mut_super_prop(&mut self, n: &mut SuperProp) {
+        if let SuperProp::Computed(computed) = n {
+            computed.visit_mut_with(self);
+        }
+    }
+    fn visit_mut_prop_name(&mut self, n: &mut PropName) {
+        if let PropName::Computed(computed) = n {
+            computed.visit_mut_with(self);
+        }
+    }
+}
@@ -0,0 +1,549 @@
+use std::collections::HashSet;
+use std::mem::replace;
+
+use swc_core::common::{sync::Lrc, util::take::Take, FileName, SourceMap, SyntaxContext, DUMMY_SP};
+use swc_core::ecma::ast::*;
+use swc_core::ecma::parser::{parse_file_as_expr, EsConfig, Syntax};
+use swc_core::ecma::utils::{drop_span, find_pat_ids, private_ident, quote_ident, ExprFactory};
+use swc_core::ecma::visit::{
+    noop_visit_mut_type, noop_visit_type, Visit, VisitMut, VisitMutWith, VisitWith,
+};
+
+/// Whether `name` is a binding of the object environment of `with (object)`.
+const HAS_BINDING: &str = r#"
+(object, name) => {
+    if (!(name in object)) return false;
+    var unscopables = object[Symbol.unscopables];
+    return !(unscopables !== null && (typeof unscopables === "object" || typeof unscopables === "function") && unscopables[name]);
+}
+"#;
+
+/// const hasBinding = (object, name) => { ... }
+pub(super) fn has_binding(ident: Ident, unresolved: SyntaxContext) -> Stmt {
+    let cm: Lrc<SourceMap> = Default::default();
+    let fm = cm.new_source_file(FileName::Anon, HAS_BINDING.into());
+    let mut expr = drop_span(
+        parse_file_as_expr(
+            &fm,
+            Syntax::Es(EsConfig::default()),
+            EsVersion::latest(),
+            None,
+            &mut vec![],
+        )
+        .expect("the `with` helper should parse"),
+    );
+    let (object, name, unscopables) = (
+        private_ident!("object"),
+        private_ident!("name"),
+        private_ident!("unscopables"),
+    );
+    expr.visit_mut_with(&mut HelperIdents(|ident: &mut Ident| match &*ident.sym {
+        "object" => *ident = object.clone(),
+        "name" => *ident = name.clone(),
+        "unscopables" => *ident = unscopables.clone(),
+        "Symbol" => ident.span.ctxt = unresolved,
+        _ => {}
+    }));
+    Stmt::Decl(Decl::Var(Box::new(VarDecl {
+        span: DUMMY_SP,
+        kind: VarDeclKind::Const,
+        declare: false,
+        decls: vec![VarDeclarator {
+            span: DUMMY_SP,
+            name: ident.into(),
+            init: Some(expr),
+            definite: false,
+        }],
+    })))
+}
+
+struct HelperIdents<F: FnMut(&mut Ident)>(F);
+impl<F: FnMut(&mut Ident)> VisitMut for HelperIdents<F> {
+    noop_visit_mut_type!();
+    fn visit_mut_ident(&mut self, n: &mut Ident) {
+        (self.0)(n);
+    }
+    fn visit_mut_member_prop(&mut self, n: &mut MemberProp) {
+        if let MemberProp::Computed(computed) = n {
+            computed.visit_mut_with(self);
+        }
+    }
+}
+
+/// with (value) body
+/// =>
+/// {
+///     var x;
+///     const _value = value;
+///     if (_value == null) throw new TypeError("Cannot convert undefined or null to object");
+///     const _object = Object(_value);
+///     body
+/// }
+///
+/// where the identifiers of `body` that are not declared in it look up `_object` first, for
+/// example `x` is `hasBinding(_object, "x") ? _object.x : x`. `var x` of the body declares `x`
+/// outside of the statement, its initializer is an assignment that looks up `_object` too.
+///
+/// Identifiers without a span are generated by the compiler and are not looked up.
+pub(super) fn rewrite_with(with: WithStmt, has_binding: &Ident, unresolved: SyntaxContext) -> Stmt {
+    let WithStmt {
+        span,
+        obj,
+        mut body,
+    } = with;
+    let mut inner = InnerBindings::default();
+    body.visit_with(&mut inner);
+    let value = private_ident!("value");
+    let object = private_ident!("object");
+    let mut rewrite = WithBody {
+        object: &object,
+        has_binding,
+        inner: inner.ids,
+        vars: vec![],
+        in_var_scope: false,
+        in_function: false,
+        assigning: false,
+    };
+    body.visit_mut_with(&mut rewrite);
+
+    let global = |sym: &str| Ident::new(sym.into(), DUMMY_SP.with_ctxt(unresolved));
+    let mut stmts = vec![];
+    if !rewrite.vars.is_empty() {
+        stmts.push(var(
+            VarDeclKind::Var,
+            rewrite
+                .vars
+                .into_iter()
+                .map(|ident| (ident, None))
+                .collect(),
+        ));
+    }
+    stmts.push(var(VarDeclKind::Const, vec![(value.clone(), Some(obj))]));
+    stmts.push(Stmt::If(IfStmt {
+        span: DUMMY_SP,
+        test: Box::new(Expr::Bin(BinExpr {
+            span: DUMMY_SP,
+            op: op!("=="),
+            left: Box::new(value.clone().into()),
+            right: Box::new(Lit::Null(Null { span: DUMMY_SP }).into()),
+        })),
+        cons: Box::new(Stmt::Throw(ThrowStmt {
+            span: DUMMY_SP,
+            arg: Box::new(Expr::New(NewExpr {
+                span: DUMMY_SP,
+                callee: Box::new(global("TypeError").into()),
+                args: Some(vec![Expr::from(
+                    "Cannot convert undefined or null to object",
+                )
+                .as_arg()]),
+                type_args: None,
+            })),
+        })),
+        alt: None,
+    }));
+    stmts.push(var(
+        VarDeclKind::Const,
+        vec![(
+            object,
+            Some(Box::new(
+                global("Object").as_call(DUMMY_SP, vec![value.as_arg()]),
+            )),
+        )],
+    ));
+    stmts.push(*body);
+    Stmt::Block(BlockStmt { span, stmts })
+}
+
+fn var(kind: VarDeclKind, decls: Vec<(Ident, Option<Box<Expr>>)>) -> Stmt {
+    Stmt::Decl(Decl::Var(Box::new(VarDecl {
+        span: DUMMY_SP,
+        kind,
+        declare: false,
+        decls: decls
+            .into_iter()
+            .map(|(name, init)| VarDeclarator {
+                span: DUMMY_SP,
+                name: name.into(),
+                init,
+                definite: false,
+            })
+            .collect(),
+    })))
+}
+
+/// The bindings declared in the body of `with`, except `var` outside of functions.
+#[derive(Default)]
+struct InnerBindings {
+    ids: HashSet<Id>,
+    in_var_scope: bool,
+}
+impl Visit for InnerBindings {
+    noop_visit_type!();
+    fn visit_binding_ident(&mut self, n: &BindingIdent) {
+        self.ids.insert(n.id.to_id());
+    }
+    fn visit_var_decl(&mut self, n: &VarDecl) {
+        if n.kind == VarDeclKind::Var && !self.in_var_scope {
+            for decl in &n.decls {
+                decl.init.visit_with(self);
+            }
+        } else {
+            n.visit_children_with(self);
+        }
+    }
+    fn visit_for_head(&mut self, n: &ForHead) {
+        if let ForHead::VarDecl(var) = n {
+            var.visit_with(self);
+        }
+    }
+    fn visit_assign_expr(&mut self, n: &AssignExpr) {
+        n.right.visit_with(self);
+    }
+    fn visit_fn_decl(&mut self, n: &FnDecl) {
+        self.ids.insert(n.ident.to_id());
+        n.function.visit_with(self);
+    }
+    fn visit_fn_expr(&mut self, n: &FnExpr) {
+        if let Some(ident) = &n.ident {
+            self.ids.insert(ident.to_id());
+        }
+        n.function.visit_with(self);
+    }
+    fn visit_class_decl(&mut self, n: &ClassDecl) {
+        self.ids.insert(n.ident.to_id());
+        n.class.visit_with(self);
+    }
+    fn visit_class_expr(&mut self, n: &ClassExpr) {
+        if let Some(ident) = &n.ident {
+            self.ids.insert(ident.to_id());
+        }
+        n.class.visit_with(self);
+    }
+    fn visit_function(&mut self, n: &Function) {
+        let old = replace(&mut self.in_var_scope, true);
+        n.visit_children_with(self);
+        self.in_var_scope = old;
+    }
+    fn visit_arrow_expr(&mut self, n: &ArrowExpr) {
+        let old = replace(&mut self.in_var_scope, true);
+        n.visit_children_with(self);
+        self.in_var_scope = old;
+    }
+    fn visit_getter_prop(&mut self, n: &GetterProp) {
+        let old = replace(&mut self.in_var_scope, true);
+        n.visit_children_with(self);
+        self.in_var_scope = old;
+    }
+    fn visit_setter_prop(&mut self, n: &SetterProp) {
+        let old = replace(&mut self.in_var_scope, true);
+        n.visit_children_with(self);
+        self.in_var_scope = old;
+    }
+    fn visit_static_block(&mut self, n: &StaticBlock) {
+        let old = replace(&mut self.in_var_scope, true);
+        n.visit_children_with(self);
+        self.in_var_scope = old;
+    }
+}
+
+struct WithBody<'a> {
+    object: &'a Ident,
+    has_binding: &'a Ident,
+    /// The bindings declared in the body, they shadow the properties of the object.
+    inner: HashSet<Id>,
+    /// The names declared by `var` in the body, outside of functions.
+    vars: Vec<Ident>,
+    /// Inside a function or an arrow function, where `var` declares a local.
+    in_var_scope: bool,
+    /// Inside a non-arrow function, which has its own `arguments`.
+    in_function: bool,
+    /// Visiting the target of an assignment.
+    assigning: bool,
+}
+impl WithBody<'_> {
+    fn is_free(&self, ident: &Ident) -> bool {
+        let arguments = self.in_function && &*ident.sym == "arguments";
+        !(ident.span.is_dummy() || self.inner.contains(&ident.to_id()) || arguments)
+    }
+    fn free_ident<'e>(&self, expr: &'e Expr) -> Option<&'e Ident> {
+        expr.as_ident().filter(|ident| self.is_free(ident))
+    }
+    fn simple_target<'e>(&self, target: &'e PatOrExpr) -> Option<&'e Ident> {
+        let ident = match target {
+            PatOrExpr::Expr(expr) => expr.as_ident(),
+            PatOrExpr::Pat(pat) => match &**pat {
+                Pat::Ident(ident) => Some(&ident.id),
+                Pat::Expr(expr) => expr.as_ident(),
+                _ => None,
+            },
+        };
+        ident.filter(|ident| self.is_free(ident))
+    }
+    /// _object.x
+    fn member(&self, ident: &Ident) -> Expr {
+        self.object
+            .clone()
+            .make_member(quote_ident!(ident.span, ident.sym.clone()))
+    }
+    /// hasBinding(_object, "x") ? cons : alt
+    fn lookup(&self, ident: &Ident, cons: Expr, alt: Expr) -> Expr {
+        Expr::Cond(CondExpr {
+            span: DUMMY_SP,
+            test: Box::new(self.has_binding.clone().as_call(
+                DUMMY_SP,
+                vec![
+                    self.object.clone().as_arg(),
+                    Expr::from(&*ident.sym).as_arg(),
+                ],
+            )),
+            cons: Box::new(cons),
+            alt: Box::new(alt),
+        })
+    }
+    /// (hasBinding(_object, "x") ? _object : { set x(value) { x = value } }).x
+    fn target(&self, ident: &Ident) -> Pat {
+        let value = private_ident!("value");
+        let setter = ObjectLit {
+            span: DUMMY_SP,
+            props: vec![PropOrSpread::Prop(Box::new(Prop::Setter(SetterProp {
+                span: DUMMY_SP,
+                key: PropName::Ident(quote_ident!(ident.sym.clone())),
+                param: Box::new(value.clone().into()),
+                body: Some(BlockStmt {
+                    span: DUMMY_SP,
+                    stmts: vec![Expr::Ident(value)
+                        .make_assign_to(op!("="), ident.clone().into())
+                        .into_stmt()],
+                }),
+            })))],
+        };
+        let object = self.lookup(ident, self.object.clone().into(), setter.into());
+        Pat::Expr(Box::new(
+            object.make_member(quote_ident!(ident.span, ident.sym.clone())),
+        ))
+    }
+    /// var a = 1, b
+    /// =>
+    /// a = 1
+    fn var_to_assign(&mut self, var: &mut VarDecl) -> Option<Box<Expr>> {
+        self.vars.extend(find_pat_ids::<_, Ident>(&var.decls));
+        let mut exprs = vec![];
+        for decl in var.decls.take() {
+            let Some(init) = decl.init else { continue };
+            let mut assign = Expr::Assign(AssignExpr {
+                span: decl.span,
+                op: op!("="),
+                left: PatOrExpr::Pat(Box::new(decl.name)),
+                right: init,
+            });
+            assign.visit_mut_with(self);
+            exprs.push(Box::new(assign));
+        }
+        match exprs.len() {
+            0 => None,
+            1 => exprs.pop(),
+            _ => Some(Box::new(Expr::Seq(SeqExpr {
+                span: var.span,
+                exprs,
+            }))),
+        }
+    }
+    fn var_scope(&mut self, in_function: bool, visit: impl FnOnce(&mut Self)) {
+        let old = (self.in_var_scope, self.in_function, self.assigning);
+        self.in_var_scope = true;
+        self.in_function |= in_function;
+        self.assigning = false;
+        visit(self);
+        (self.in_var_scope, self.in_function, self.assigning) = old;
+    }
+}
+impl VisitMut for WithBody<'_> {
+    noop_visit_mut_type!();
+    fn visit_mut_expr(&mut self, n: &mut Expr) {
+        match n {
+            Expr::Ident(ident) if self.is_free(ident) => {
+                let ident = ident.clone();
+                *n = self.lookup(&ident, self.member(&ident), ident.clone().into());
+            }
+            Expr::Call(CallExpr {
+                callee: Callee::Expr(callee),
+                args,
+                ..
+            }) if self.free_ident(callee).is_some() => {
+                let ident = callee.as_ident().unwrap().clone();
+                args.visit_mut_with(self);
+                let mut call = n.take();
+                let mut member_call = call.clone();
+                if let Expr::Call(member_call) = &mut member_call {
+                    member_call.callee = Callee::Expr(Box::new(self.member(&ident)));
+                }
+                if let Expr::Call(call) = &mut call {
+                    call.callee = Callee::Expr(Box::new(ident.clone().into()));
+                }
+                *n = self.lookup(&ident, member_call, call);
+            }
+            Expr::TaggedTpl(TaggedTpl { tag, tpl, .. }) if self.free_ident(tag).is_some() => {
+                let ident = tag.as_ident().unwrap().clone();
+                tpl.visit_mut_with(self);
+                let mut member_tagged = n.clone();
+                if let Expr::TaggedTpl(member_tagged) = &mut member_tagged {
+                    *member_tagged.tag = self.member(&ident);
+                }
+                *n = self.lookup(&ident, member_tagged, n.take());
+            }
+            Expr::Assign(assign) if self.simple_target(&assign.left).is_some() => {
+                let ident = self.simple_target(&assign.left).unwrap().clone();
+                assign.right.visit_mut_with(self);
+                let mut member_assign = assign.clone();
+                member_assign.left = PatOrExpr::Expr(Box::new(self.member(&ident)));
+                *n = self.lookup(&ident, member_assign.into(), n.take());
+            }
+            Expr::Update(update) if self.free_ident(&update.arg).is_some() => {
+                let ident = update.arg.as_ident().unwrap().clone();
+                let mut member_update = update.clone();
+                member_update.arg = Box::new(self.member(&ident));
+                *n = self.lookup(&ident, member_update.into(), n.take());
+            }
+            Expr::Unary(unary)
+                if matches!(unary.op, op!("typeof") | op!("delete"))
+                    && self.free_ident(&unary.arg).is_some() =>
+            {
+                let ident = unary.arg.as_ident().unwrap().clone();
+                let mut member_unary = unary.clone();
+                member_unary.arg = Box::new(self.member(&ident));
+                *n = self.lookup(&ident, member_unary.into(), n.take());
+            }
+            _ => n.visit_mut_children_with(self),
+        }
+    }
+    fn visit_mut_assign_expr(&mut self, n: &mut AssignExpr) {
+        let assigning = replace(&mut self.assigning, true);
+        n.left.visit_mut_with(self);
+        self.assigning = assigning;
+        n.right.visit_mut_with(self);
+    }
+    fn visit_mut_pat(&mut self, n: &mut Pat) {
+        if self.assigning {
+            let ident = match n {
+                Pat::Ident(ident) => Some(&ident.id),
+                Pat::Expr(expr) => expr.as_ident(),
+                _ => None,
+            };
+            if let Some(ident) = ident.filter(|ident| self.is_free(ident)).cloned() {
+                *n = self.target(&ident);
+                return;
+            }
+        }
+        n.visit_mut_children_with(self);
+    }
+    fn visit_mut_object_pat_prop(&mut self, n: &mut ObjectPatProp) {
+        n.visit_mut_children_with(self);
+        if let ObjectPatProp::Assign(AssignPatProp { span, key, value }) = n {
+            if !self.assigning || !self.is_free(key) {
+                return;
+            }
+            let target = Box::new(self.target(key));
+            *n = ObjectPatProp::KeyValue(KeyValuePatProp {
+                key: PropName::Ident(quote_ident!(key.span, key.sym.clone())),
+                value: match value.take() {
+                    Some(value) => Box::new(Pat::Assign(AssignPat {
+                        span: *span,
+                        left: target,
+                        right: value,
+                    })),
+                    None => target,
+                },
+            });
+        }
+    }
+    fn visit_mut_prop(&mut self, n: &mut Prop) {
+        if let Prop::Shorthand(ident) = n {
+            if self.is_free(ident) {
+                let ident = ident.clone();
+                *n = Prop::KeyValue(KeyValueProp {
+                    key: PropName::Ident(quote_ident!(ident.span, ident.sym.clone())),
+                    value: Box::new(self.lookup(&ident, self.member(&ident), ident.clone().into())),
+                });
+                return;
+            }
+        }
+        n.visit_mut_children_with(self);
+    }
+    fn visit_mut_stmt(&mut self, n: &mut Stmt) {
+        if let Stmt::Decl(Decl::Var(var)) = n {
+            if var.kind == VarDeclKind::Var && !self.in_var_scope {
+                let span = var.span;
+                *n = match self.var_to_assign(var) {
+                    Some(expr) => expr.into_stmt(),
+                    None => Stmt::Empty(EmptyStmt { span }),
+                };
+                return;
+            }
+        }
+        n.visit_mut_children_with(self);
+    }
+    fn visit_mut_for_head(&mut self, n: &mut ForHead) {
+        if let ForHead::VarDecl(var) = n {
+            if var.kind == VarDeclKind::Var && var.decls.len() == 1 && !self.in_var_scope {
+                self.vars.extend(find_pat_ids::<_, Ident>(&var.decls));
+                *n = ForHead::Pat(Box::new(var.decls.take().remove(0).name));
+            }
+        }
+        match n {
+            ForHead::Pat(pat) => {
+                let assigning = replace(&mut self.assigning, true);
+                pat.visit_mut_with(self);
+                self.assigning = assigning;
+            }
+            _ => n.visit_mut_children_with(self),
+        }
+    }
+    fn visit_mut_for_stmt(&mut self, n: &mut ForStmt) {
+        match &mut n.init {
+            Some(VarDeclOrExpr::VarDecl(var))
+                if var.kind == VarDeclKind::Var && !self.in_var_scope =>
+            {
+                n.init = self.var_to_assign(var).map(VarDeclOrExpr::Expr);
+            }
+            init => init.visit_mut_with(self),
+        }
+        n.test.visit_mut_with(self);
+        n.update.visit_mut_with(self);
+        n.body.visit_mut_with(self);
+    }
+    fn visit_mut_function(&mut self, n: &mut Function) {
+        self.var_scope(true, |this| n.visit_mut_children_with(this));
+    }
+    fn visit_mut_getter_prop(&mut self, n: &mut GetterProp) {
+        n.key.visit_mut_with(self);
+        self.var_scope(true, |this| n.body.visit_mut_with(this));
+    }
+    fn visit_mut_setter_prop(&mut self, n: &mut SetterProp) {
+        n.key.visit_mut_with(self);
+        self.var_scope(true, |this| {
+            n.param.visit_mut_with(this);
+            n.body.visit_mut_with(this);
+        });
+    }
+    fn visit_mut_arrow_expr(&mut self, n: &mut ArrowExpr) {
+        self.var_scope(false, |this| n.visit_mut_children_with(this));
+    }
+    fn visit_mut_static_block(&mut self, n: &mut StaticBlock) {
+        self.var_scope(true, |this| n.visit_mut_children_with(this));
+    }
+    fn visit_mut_member_prop(&mut self, n: &mut MemberProp) {
+        if let MemberProp::Computed(computed) = n {
+            computed.visit_mut_with(self);
+        }
+    }
+    fn visit_mut_super_prop(&mut self, n: &mut SuperProp) {
+        if let SuperProp::Computed(computed) = n {
+            computed.visit_mut_with(self);
+        }
+    }
+    fn visit_mut_prop_name(&mut self, n: &mut PropName) {
+        if let PropName::Computed(computed) = n {
+            computed.visit_mut_with(self);
+        }
+    }
+}
@@ -0,0 +1,247 @@
+// A compartment that links and evaluates VirtualModuleSource records with the module semantics of
+// the specification: exports are in the TDZ until the record initializes them, `export *` names
+// provided by more than one module are ambiguous, and specifiers are resolved like URL paths. It
+// follows @masknet/compartment, without its loader and global object.
+'use strict'
+
+// `context.evaluate(scope, code)`: a strict direct eval that sees the bindings of `scope` first.
+// `with` is only allowed in sloppy functions, hence the Function constructor. The code is `this`,
+// every name in the `with` body is looked up in `scope` first, including `arguments`.
+const evaluateInScope = Function('with (arguments[0]) return eval(\'"use strict";\' + this)')
+
+const ambiguous = Symbol('ambiguous')
+// The binding name of `export * as ns from` and of `import * as ns from`.
+const namespaceBinding = Symbol('namespace')
+
+/**
+ * @param {string} root directory of the entry module
+ * @param {(path: string) => string} compileRecord compiles the file at path with the `eval` template
+ */
+function createCompartment(root, compileRecord) {
+    const modules = new Map()
+
+    // Resolve `specifier` against the key of `referrer` like the path of a URL, keys are relative
+    // to `root`, like `lib/a.js`.
+    function resolveKey(referrer, specifier) {
+        const segments = specifier.startsWith('/') ? [] : referrer.split('/').slice(0, -1)
+        for (const segment of specifier.split('/')) {
+            if (segment === '' || segment === '.') continue
+            if (segment === '..' && segments.length && segments[segments.length - 1] !== '..') segments.pop()
+            else segments.push(segment)
+        }
+        return segments.join('/')
+    }
+
+    function load(key) {
+        let module = modules.get(key)
+        if (module) return module
+
+        module = {
+            key,
+            record: (0, eval)(compileRecord(root + '/' + key)),
+            // The modules of the specifiers, in the order they are requested.
+            requested: new Map(),
+            imports: [],
+            localExports: [],
+            indirectExports: [],
+            starExports: [],
+            values: new Map(),
+            env: undefined,
+            namespace: undefined,
+            status: 'new',
+        }
+        modules.set(key, module)
+
+        const request = (specifier) => {
+            if (!module.requested.has(specifier)) module.requested.set(specifier, undefined)
+            return specifier
+        }
+        for (const binding of module.record.bindings || []) {
+            if ('importAllFrom' in binding) {
+                module.imports.push({ specifier: request(binding.importAllFrom), name: namespaceBinding, as: binding.as })
+            } else if ('import' in binding) {
+                const specifier = request(binding.from)
+                module.imports.push({ specifier, name: binding.import, as: binding.as ?? binding.import })
+            } else if ('exportAllFrom' in binding) {
+                const specifier = request(binding.exportAllFrom)
+                if (binding.as === undefined) module.starExports.push(specifier)
+                else module.indirectExports.push({ specifier, name: binding.as, importName: namespaceBinding })
+            } else if (binding.from !== undefined) {
+                const specifier = request(binding.from)
+                module.indirectExports.push({ specifier, name: binding.as ?? binding.export, importName: binding.export })
+            } else {
+                module.localExports.push(binding.export)
+            }
+        }
+        for (const specifier of module.requested.keys()) {
+            module.requested.set(specifier, load(resolveKey(key, specifier)))
+        }
+        return module
+    }
+
+    /** https://tc39.es/ecma262/#sec-getexportednames */
+    function getExportedNames(module, exportStarSet = []) {
+        if (exportStarSet.includes(module)) return []
+        exportStarSet.push(module)
+        const names = [...module.localExports, ...module.indirectExports.map((e) => e.name)]
+        for (const specifier of module.starExports) {
+            for (const name of getExportedNames(module.requested.get(specifier), exportStarSet)) {
+                if (name !== 'default' && !names.includes(name)) names.push(name)
+            }
+        }
+        return names
+    }
+
+    /** https://tc39.es/ecma262/#sec-resolveexport */
+    function resolveExport(module, exportName, resolveSet = []) {
+        if (resolveSet.some((r) => r.module === module && r.exportName === exportName)) return null
+        resolveSet.push({ module, exportName })
+        if (module.localExports.includes(exportName)) return { module, bindingName: exportName }
+        for (const e of module.indirectExports) {
+            if (e.name !== exportName) continue
+            const target = module.requested.get(e.specifier)
+            if (e.importName === namespaceBinding) return { module: target, bindingName: namespaceBinding }
+            return resolveExport(target, e.importName, resolveSet)
+        }
+        if (exportName === 'default') return null
+        let starResolution = null
+        for (const specifier of module.starExports) {
+            const resolution = resolveExport(module.requested.get(specifier), exportName, resolveSet)
+            if (resolution === ambiguous) return ambiguous
+            if (resolution === null) continue
+            if (starResolution === null) starResolution = resolution
+            else if (
+                resolution.module !== starResolution.module ||
+                resolution.bindingName !== starResolution.bindingName
+            ) {
+                return ambiguous
+            }
+        }
+        return starResolution
+    }
+
+    function readBinding({ module, bindingName }) {
+        if (bindingName === namespaceBinding) return getNamespace(module)
+        if (!module.values.has(bindingName)) {
+            throw new ReferenceError(`Cannot access '${bindingName}' before initialization`)
+        }
+        return module.values.get(bindingName)
+    }
+
+    /** https://tc39.es/ecma262/#sec-module-namespace-exotic-objects */
+    function getNamespace(module) {
+        if (module.namespace) return module.namespace
+        const resolutions = new Map()
+        for (const name of getExportedNames(module).sort()) {
+            const resolution = resolveExport(module, name)
+            if (resolution !== null && resolution !== ambiguous) resolutions.set(name, resolution)
+        }
+        const target = Object.create(null)
+        for (const name of resolutions.keys()) {
+            Object.defineProperty(target, name, { value: undefined, writable: true, enumerable: true })
+        }
+        Object.defineProperty(target, Symbol.toStringTag, { value: 'Module' })
+        Object.preventExtensions(target)
+        const isExport = (key) => typeof key === 'string' && resolutions.has(key)
+        module.namespace = new Proxy(target, {
+            get: (target, key) => {
+                if (typeof key === 'symbol') return Reflect.get(target, key)
+                return resolutions.has(key) ? readBinding(resolutions.get(key)) : undefined
+            },
+            getOwnPropertyDescriptor: (target, key) => {
+                if (!isExport(key)) return Reflect.getOwnPropertyDescriptor(target, key)
+                const value = readBinding(resolutions.get(key))
+                return { value, writable: true, enumerable: true, configurable: false }
+            },
+            defineProperty: (target, key, descriptor) => {
+                if (!isExport(key)) return Reflect.defineProperty(target, key, descriptor)
+                const value = readBinding(resolutions.get(key))
+                if (descriptor.configurable === true || descriptor.enumerable === false) return false
+                if ('get' in descriptor || 'set' in descriptor || descriptor.writable === false) return false
+                return !('value' in descriptor) || Object.is(descriptor.value, value)
+            },
+            has: (target, key) => (typeof key === 'symbol' ? Reflect.has(target, key) : resolutions.has(key)),
+            set: () => false,
+            deleteProperty: (target, key) => (isExport(key) ? false : Reflect.deleteProperty(target, key)),
+        })
+        return module.namespace
+    }
+
+    /** https://tc39.es/ecma262/#sec-source-text-module-record-initialize-environment */
+    function initializeEnvironment(module) {
+        for (const e of module.indirectExports) {
+            const resolution = resolveExport(module, e.name)
+            if (resolution === null || resolution === ambiguous) {
+                throw new SyntaxError(`${e.specifier} does not provide an unambiguous export named ${e.name}`)
+            }
+        }
+        const env = Object.create(null)
+        for (const { specifier, name, as } of module.imports) {
+            const target = module.requested.get(specifier)
+            if (name === namespaceBinding) {
+                Object.defineProperty(env, as, { get: () => getNamespace(target) })
+                continue
+            }
+            const resolution = resolveExport(target, name)
+            if (resolution === null) throw new SyntaxError(`${specifier} does not provide an export named ${name}`)
+            if (resolution === ambiguous) {
+                throw new SyntaxError(`${specifier} does not provide an unambiguous export named ${name}`)
+            }
+            Object.defineProperty(env, as, { get: () => readBinding(resolution) })
+        }
+        for (const name of module.localExports) {
+            Object.defineProperty(env, name, {
+                get: () => module.values.get(name),
+                set: (value) => module.values.set(name, value),
+            })
+        }
+        module.env = env
+    }
+
+    function link(module) {
+        if (module.status !== 'new') return
+        module.status = 'linking'
+        try {
+            for (const dependency of module.requested.values()) link(dependency)
+            initializeEnvironment(module)
+        } catch (error) {
+            module.status = 'new'
+            throw error
+        }
+        module.status = 'linked'
+    }
+
+    async function evaluate(module) {
+        if (module.status === 'evaluated') {
+            if ('error' in module) throw module.error
+            return
+        }
+        // A cycle, the module is evaluated by the caller up the stack.
+        if (module.status === 'evaluating') return
+        module.status = 'evaluating'
+        try {
+            for (const dependency of module.requested.values()) await evaluate(dependency)
+            const context = {
+                globalThis,
+                importMeta: Object.assign(Object.create(null), { url: module.key }),
+                import: (specifier) => importModule(resolveKey(module.key, specifier)),
+                evaluate: (scope, code) => evaluateInScope.call(code, scope),
+            }
+            if (module.record.execute) await module.record.execute(module.env, context)
+        } catch (error) {
+            module.error = error
+            throw error
+        } finally {
+            module.status = 'evaluated'
+        }
+    }
+
+    async function importModule(key) {
+        const module = load(key)
+        link(module)
+        await evaluate(module)
+        return getNamespace(module)
+    }
+
+    return (specifier) => importModule(resolveKey('', specifier))
+}
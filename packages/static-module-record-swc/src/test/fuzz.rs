@@ -0,0 +1,154 @@
+use std::fs::write;
+
+use proptest::prelude::*;
+use proptest::sample::subsequence;
+use swc_core::common::{sync::Lrc, FileName, SourceMap};
+use swc_core::ecma::ast::EsVersion;
+use swc_core::ecma::parser::{parse_file_as_module, Syntax};
+use tempfile::TempDir;
+
+use super::semantics::{run_native, run_record};
+use crate::compile_module;
+
+const DEPENDENCY: &str = "
+export let a = 1
+export const b = 2
+export default 'dep'
+export function bump() {
+    a++
+}
+";
+
+/// Every name read by the statements is imported, unresolved names would become global lookups.
+fn imports() -> impl Strategy<Value = Vec<&'static str>> {
+    Just(vec![
+        "import { a as x } from './_dep.js'",
+        "import { b, bump } from './_dep.js'",
+        "import def from './_dep.js'",
+        "import * as ns from './_dep.js'",
+        "import './_dep.js'",
+    ])
+    .prop_shuffle()
+}
+
+fn declaration(name: &'static str) -> impl Strategy<Value = String> {
+    let export = prop_oneof![Just(""), Just("export ")];
+    let declaration = prop_oneof![
+        Just(format!("var {name} = 1")),
+        Just(format!("let {name} = 1")),
+        Just(format!("let {name}")),
+        Just(format!("const {name} = 1")),
+        Just(format!("var [{name}] = [1, 2]")),
+        Just(format!("let {{ {name} = 2, ...rest_{name} }} = {{}}")),
+        Just(format!("let [, {{ key: {name} }}] = [0, {{ key: 3 }}]")),
+        Just(format!("function {name}() {{ return 1 }}")),
+        Just(format!("class {name} {{}}")),
+    ];
+    (export, declaration).prop_map(|(export, declaration)| format!("{export}{declaration}"))
+}
+
+fn statement() -> impl Strategy<Value = String> {
+    let name = prop_oneof![Just("p"), Just("q"), Just("r")];
+    let other = prop_oneof![Just("p"), Just("q"), Just("r")];
+    let read = prop_oneof![
+        Just("p"),
+        Just("q"),
+        Just("x"),
+        Just("b"),
+        Just("def"),
+        Just("ns.a")
+    ];
+    (name, other, read).prop_flat_map(|(name, other, read)| {
+        prop_oneof![
+            Just(format!("{name}++")),
+            Just(format!("--{name}")),
+            Just(format!("{name} += 2")),
+            Just(format!("{name} = {read}")),
+            Just(format!("[{name}, {other}] = [{other}, {name}]")),
+            Just(format!("({{ {name} }} = {{ {name}: 5 }})")),
+            Just(format!("({{ key: {name} = 6 }} = {{}})")),
+            Just(format!("for (var i = 0; i < 2; i++) {name} += i")),
+            Just(format!("for ({name} of [3, 4]);")),
+            Just(format!("for ({name} in {{ key: 1 }}) {{}}")),
+            Just(format!("for (const v of [5, 6]) {name} = v")),
+            Just(format!("{{ let {name} = 0; {name}++ }}")),
+            Just(format!("if ({name}) {name} = 0; else {name} = 1")),
+            Just(format!("log.push({read})")),
+            Just("bump()".to_string()),
+            Just(format!("log.push(typeof {name})")),
+        ]
+    })
+}
+
+fn exports() -> impl Strategy<Value = Vec<&'static str>> {
+    subsequence(
+        vec![
+            "export { p as renamed }",
+            "export { q, r as 'string name' }",
+            "export { x }",
+            "export { x as y } from './_dep.js'",
+            "export { x } from './_dep.js'",
+            "export { default as d } from './_dep.js'",
+            "export { b as c } from './_dep.js'",
+            "export * from './_dep.js'",
+            "export * as star from './_dep.js'",
+            "export default p",
+            "export function run() { p = 'updated'; bump() }",
+        ],
+        0..=11,
+    )
+}
+
+prop_compose! {
+    fn module()(
+        imports in imports(),
+        declarations in (declaration("p"), declaration("q"), declaration("r")),
+        statements in prop::collection::vec(statement(), 0..8),
+        exports in exports(),
+    ) -> String {
+        let (p, q, r) = declarations;
+        imports
+            .iter()
+            .map(|x| x.to_string())
+            .chain([p, q, r])
+            .chain(statements)
+            .chain(exports.iter().map(|x| x.to_string()))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    /// Random modules must compile without panicking into code that parses and evaluates like
+    /// the original module.
+    #[test]
+    fn fuzz(source in module()) {
+        // Removed at the end of the case.
+        let dir = TempDir::with_prefix("static-module-record-fuzz-").unwrap();
+        let entry = dir.path().join("entry.js");
+        write(&entry, &source).unwrap();
+        write(dir.path().join("_dep.js"), DEPENDENCY).unwrap();
+
+        let Ok(output) = compile_module(&source, "entry.js", Default::default()) else {
+            // Invalid modules (redeclarations, duplicated exports...) are reported, not compiled.
+            return Ok(());
+        };
+        let cm: Lrc<SourceMap> = Default::default();
+        let fm = cm.new_source_file(FileName::Anon, output.clone());
+        prop_assert!(
+            parse_file_as_module(&fm, Syntax::default(), EsVersion::latest(), None, &mut vec![])
+                .is_ok(),
+            "output does not parse:\n{}",
+            output
+        );
+
+        let Ok(native) = run_native(&entry) else {
+            // Early errors the parser of swc does not report, like exporting an undeclared name.
+            return Ok(());
+        };
+        let record = run_record(&entry).unwrap();
+        prop_assert_eq!(native, record, "source:\n{}\noutput:\n{}", source, output);
+    }
+}
@@ -0,0 +1,46 @@
+// Collects everything a semantic test compares between the native module and the record.
+'use strict'
+
+globalThis.log = []
+
+function describe(value) {
+    if (typeof value === 'function') return `[Function: ${value.name}]`
+    if (typeof value === 'string') return JSON.stringify(value)
+    if (typeof value === 'object' && value !== null) return JSON.stringify(value)
+    return String(value)
+}
+
+function observe(namespace) {
+    const result = {}
+    for (const key of Object.keys(namespace).sort()) {
+        try {
+            result[key] = describe(namespace[key])
+        } catch (error) {
+            result[key] = `<${error.name}>`
+        }
+    }
+    return result
+}
+
+function report(namespace, error) {
+    const result = {}
+    if (error === undefined) {
+        result.exports = observe(namespace)
+        // live bindings: `run` mutates the module state, the namespace must reflect it.
+        if (typeof namespace.run === 'function') {
+            try {
+                namespace.run()
+                result.afterRun = observe(namespace)
+            } catch (error) {
+                result.runError = String(error)
+            }
+        }
+    } else if (error instanceof SyntaxError) {
+        // early and link errors, their messages are defined by the host.
+        result.error = error.name
+    } else {
+        result.error = String(error)
+    }
+    result.log = log.map(describe)
+    return JSON.stringify(result, undefined, 4)
+}
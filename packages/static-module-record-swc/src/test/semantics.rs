@@ -0,0 +1,445 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs::read_to_string;
+use std::panic::catch_unwind;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use boa_engine::builtins::promise::PromiseState;
+use boa_engine::module::{ModuleLoader, Referrer};
+use boa_engine::object::builtins::JsPromise;
+use boa_engine::{
+    js_string, Context, JsError, JsNativeError, JsResult, JsString, JsValue, Module,
+    NativeFunction, Source,
+};
+
+use super::{parse_config, transform};
+use crate::module::config::{Config, Template};
+
+const OBSERVE: &str = include_str!("observe.js");
+const COMPARTMENT: &str = include_str!("compartment.js");
+
+/// Every module in `tests/semantics` is evaluated twice: once as a native ES module and once as
+/// the compiled VirtualModuleRecord linked by `compartment.js`. Both runs must observe the same
+/// exported values, side-effect order (`log`), thrown errors, TDZ and live-binding updates.
+///
+/// Files starting with `_` are dependencies of other fixtures and are not evaluated on their own.
+#[testing::fixture("tests/semantics/[!_]*.js")]
+fn semantics(input: PathBuf) {
+    let native = run_native(&input).unwrap_or_else(|err| panic!("native: {}", err));
+    let record = run_record(&input).unwrap_or_else(|err| panic!("record: {}", err));
+    assert_eq!(
+        native,
+        record,
+        "the compiled record of {} behaves differently from the native module",
+        input.display()
+    );
+}
+
+/// boa does not support top-level `using`, so the record is checked against the expected order of
+/// disposal and the SuppressedError chain instead of a native module.
+#[test]
+fn top_level_using() {
+    let context = &mut Context::default();
+    context.eval(Source::from_bytes(OBSERVE)).unwrap();
+    let state = import_module(context, Path::new("tests/semantics/_using.js")).unwrap();
+    let PromiseState::Rejected(error) = state else {
+        panic!("the module should throw");
+    };
+    let describe = context
+        .eval(Source::from_bytes(
+            "(e) => JSON.stringify([log, e.name, e.error.message, e.suppressed.name, \
+                e.suppressed.error.message, e.suppressed.suppressed.message])",
+        ))
+        .unwrap();
+    let result = describe
+        .as_callable()
+        .unwrap()
+        .call(&JsValue::undefined(), &[error], context)
+        .unwrap();
+    assert_eq!(
+        result.to_string(context).unwrap().to_std_string_escaped(),
+        r#"[["body","dispose c","async dispose b","dispose a"],"SuppressedError","a","SuppressedError","c","body"]"#
+    );
+}
+
+/// `using` in blocks and functions is disposed when they complete. An exported function is
+/// initialized even when the body throws before its declaration.
+#[test]
+fn block_using() {
+    let path = Path::new("tests/semantics/_using-block.js");
+    let record = transform(
+        path,
+        &read_to_string(path).unwrap(),
+        Config {
+            template: Template::Eval,
+            ..Default::default()
+        },
+    );
+    let context = &mut Context::default();
+    context.eval(Source::from_bytes(OBSERVE)).unwrap();
+    let result = context
+        .eval(Source::from_bytes(&format!(
+            "const env = {{}}; \
+                try {{ (0, eval)({}).execute(env, {{ globalThis }}) }} catch (e) {{ log.push(e.message) }} \
+                JSON.stringify([log, typeof env.hoisted])",
+            serde_json::to_string(&record).unwrap()
+        )))
+        .unwrap();
+    assert_eq!(
+        result.as_string().unwrap().to_std_string_escaped(),
+        r#"[["block","dispose b","function","dispose c","returned","dispose a","body"],"function"]"#
+    );
+}
+
+/// Exported globals are their values, and mutable ones are updated after each call of an
+/// exported function. boa has no WebAssembly, so the instance is a stub with the exports of
+/// `wasm-global.wasm`.
+#[test]
+fn wasm_global() {
+    let record = crate::compile_wasm(
+        &std::fs::read("tests/fixture/wasm-global.wasm").unwrap(),
+        "wasm-global.wasm",
+        Config {
+            template: Template::Eval,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    let context = &mut Context::default();
+    context
+        .eval(Source::from_bytes(&format!(
+            "const counter = {{ value: 0 }}; \
+                const increment = function () {{ counter.value++ }}; \
+                const exports = {{ increment, counter, answer: {{ value: 42 }} }}; \
+                globalThis.WebAssembly = {{ instantiate: async () => ({{ instance: {{ exports }} }}) }}; \
+                const env = {{}}; \
+                (0, eval)({}).execute(env, {{ globalThis }})",
+            serde_json::to_string(&record).unwrap()
+        )))
+        .unwrap();
+    context.run_jobs();
+    let result = context
+        .eval(Source::from_bytes(
+            "const before = env.counter; env.increment(); env.increment(); \
+                JSON.stringify([before, env.counter, env.answer, env.increment.name, env.increment.length])",
+        ))
+        .unwrap();
+    assert_eq!(
+        result.as_string().unwrap().to_std_string_escaped(),
+        r#"[0,2,42,"increment",0]"#
+    );
+}
+
+/// Top-level declarations of a classic script are properties of globalThis, the record must define
+/// them the same way when it is executed with the global object of the realm.
+///
+/// Top-level `let` is not compared, the record keeps it local instead of in the global lexical
+/// scope.
+#[test]
+fn classic_script() {
+    const PRELUDE: &str = "globalThis.existing = 'existing'";
+    const OBSERVE: &str = "JSON.stringify([log, existing, declared, destructured, typeof hoisted, \
+        later, i, nested, implicit, sloppy, Object.getOwnPropertyDescriptor(globalThis, 'later'), \
+        Object.getOwnPropertyDescriptor(globalThis, 'hoisted').configurable])";
+    let path = Path::new("tests/semantics/_script.js");
+    let source = read_to_string(path).unwrap();
+
+    let native = &mut Context::default();
+    native.eval(Source::from_bytes(self::OBSERVE)).unwrap();
+    native.eval(Source::from_bytes(PRELUDE)).unwrap();
+    native.eval(Source::from_bytes(&source)).unwrap();
+    let native = native.eval(Source::from_bytes(OBSERVE)).unwrap();
+
+    let record = crate::compile_script(
+        &source,
+        "_script.js",
+        Config {
+            template: Template::Eval,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    let context = &mut Context::default();
+    context.eval(Source::from_bytes(self::OBSERVE)).unwrap();
+    context.eval(Source::from_bytes(PRELUDE)).unwrap();
+    context
+        .eval(Source::from_bytes(&format!(
+            "(0, eval)({}).execute({{}}, {{ globalThis }})",
+            serde_json::to_string(&record).unwrap()
+        )))
+        .unwrap();
+    let record = context.eval(Source::from_bytes(OBSERVE)).unwrap();
+
+    assert_eq!(
+        native.as_string().unwrap().to_std_string_escaped(),
+        record.as_string().unwrap().to_std_string_escaped()
+    );
+}
+
+/// Sloppy mode code that the record rewrites into strict mode code keeps its meaning.
+#[test]
+fn sloppy_script() {
+    let path = Path::new("tests/semantics/_sloppy.js");
+    let source = read_to_string(path).unwrap();
+
+    let native = &mut Context::default();
+    native.eval(Source::from_bytes(&source)).unwrap();
+    let native = native
+        .eval(Source::from_bytes("JSON.stringify(result)"))
+        .unwrap();
+
+    let record = crate::compile_script(
+        &source,
+        "_sloppy.js",
+        Config {
+            template: Template::Eval,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    let context = &mut Context::default();
+    context
+        .eval(Source::from_bytes(&format!(
+            "(0, eval)({}).execute({{}}, {{ globalThis }})",
+            serde_json::to_string(&record).unwrap()
+        )))
+        .unwrap();
+    let record = context
+        .eval(Source::from_bytes("JSON.stringify(result)"))
+        .unwrap();
+
+    assert_eq!(
+        native.as_string().unwrap().to_std_string_escaped(),
+        record.as_string().unwrap().to_std_string_escaped()
+    );
+}
+
+/// Execute `source` as a classic script record with the global object of a new realm, and
+/// evaluate `observe` in the realm.
+fn execute_script(source: &str, observe: &str) -> String {
+    let record = crate::compile_script(
+        source,
+        "script.js",
+        Config {
+            template: Template::Eval,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    let context = &mut Context::default();
+    context
+        .eval(Source::from_bytes(&format!(
+            "(0, eval)({}).execute({{}}, {{ globalThis }})",
+            serde_json::to_string(&record).unwrap()
+        )))
+        .unwrap();
+    let result = context.eval(Source::from_bytes(observe)).unwrap();
+    result.as_string().unwrap().to_std_string_escaped()
+}
+
+/// Function declarations in blocks are also `var` bindings (Annex B.3.3), which boa does not
+/// implement, so the results are the ones of the specification.
+#[test]
+fn script_block_functions() {
+    let source = r#"
+        var result = []
+        function blocks(flag) {
+            var before = typeof inner
+            if (flag) {
+                function inner() { return 'inner' }
+            }
+            label: function labeled() { return 'labeled' }
+            let shadowed = 'let'
+            {
+                function shadowed() {}
+            }
+            return [before, inner(), labeled(), shadowed]
+        }
+        result.push(blocks(true))
+        result.push(typeof blockGlobal)
+        {
+            result.push(blockGlobal())
+            function blockGlobal() { return 'global' }
+        }
+        result.push(blockGlobal(), Object.getOwnPropertyDescriptor(globalThis, 'blockGlobal').configurable)
+    "#;
+    assert_eq!(
+        execute_script(source, "JSON.stringify(result)"),
+        r#"[["undefined","inner","labeled","let"],"undefined","global","global",false]"#
+    );
+}
+
+/// Top-level `let`, `const` and `class` of a record stay local to the record instead of going to
+/// the global lexical scope of the realm, other scripts do not see them.
+#[test]
+fn script_lexical_scope() {
+    let source = "let lexical = 1; const constant = 2; class Class {} var global = lexical";
+    assert_eq!(
+        execute_script(
+            source,
+            "JSON.stringify([typeof lexical, typeof constant, typeof Class, global])"
+        ),
+        r#"["undefined","undefined","undefined",1]"#
+    );
+}
+
+pub(super) fn run_native(entry: &Path) -> JsResult<String> {
+    let root = entry.parent().unwrap();
+    let loader = Rc::new(RelativeModuleLoader::new(root));
+    let context = &mut Context::builder().module_loader(loader.clone()).build()?;
+    context.eval(Source::from_bytes(OBSERVE))?;
+
+    let source = Source::from_filepath(entry).map_err(io_error)?;
+    let module = Module::parse(source, None, context)?;
+    loader.insert(entry.canonicalize().map_err(io_error)?, module.clone());
+
+    let promise = module.load_link_evaluate(context);
+    context.run_jobs();
+    let state = promise.state();
+    let namespace = match &state {
+        PromiseState::Fulfilled(_) => module.namespace(context).into(),
+        _ => JsValue::undefined(),
+    };
+    report(context, state, namespace)
+}
+
+pub(super) fn run_record(entry: &Path) -> JsResult<String> {
+    let context = &mut Context::default();
+    context.eval(Source::from_bytes(OBSERVE))?;
+    let state = import_module(context, entry)?;
+    let namespace = match &state {
+        PromiseState::Fulfilled(namespace) => namespace.clone(),
+        _ => JsValue::undefined(),
+    };
+    report(context, state, namespace)
+}
+
+/// Resolves specifiers against the path of the importing module, `SimpleModuleLoader` of boa
+/// resolves them against the root.
+struct RelativeModuleLoader {
+    root: PathBuf,
+    paths: RefCell<HashMap<Module, PathBuf>>,
+    modules: RefCell<HashMap<PathBuf, Module>>,
+}
+
+impl RelativeModuleLoader {
+    fn new(root: &Path) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            paths: Default::default(),
+            modules: Default::default(),
+        }
+    }
+    fn insert(&self, path: PathBuf, module: Module) {
+        self.paths.borrow_mut().insert(module.clone(), path.clone());
+        self.modules.borrow_mut().insert(path, module);
+    }
+    fn load(
+        &self,
+        referrer: &Referrer,
+        specifier: &str,
+        context: &mut Context,
+    ) -> JsResult<Module> {
+        let base = match referrer {
+            Referrer::Module(module) => self.paths.borrow()[module].parent().unwrap().to_path_buf(),
+            _ => self.root.clone(),
+        };
+        let path = base.join(specifier).canonicalize().map_err(io_error)?;
+        if let Some(module) = self.modules.borrow().get(&path) {
+            return Ok(module.clone());
+        }
+        let source = Source::from_filepath(&path).map_err(io_error)?;
+        let module = Module::parse(source, None, context)?;
+        self.insert(path, module.clone());
+        Ok(module)
+    }
+}
+
+impl ModuleLoader for RelativeModuleLoader {
+    fn load_imported_module(
+        &self,
+        referrer: Referrer,
+        specifier: JsString,
+        finish_load: Box<dyn FnOnce(JsResult<Module>, &mut Context)>,
+        context: &mut Context,
+    ) {
+        let result = self.load(&referrer, &specifier.to_std_string_escaped(), context);
+        finish_load(result, context);
+    }
+}
+
+/// Import `entry` as a record through `compartment.js` and run the jobs until it settles.
+pub(super) fn import_module(context: &mut Context, entry: &Path) -> JsResult<PromiseState> {
+    let root = entry.parent().unwrap().canonicalize().map_err(io_error)?;
+    context.eval(Source::from_bytes(COMPARTMENT))?;
+    context.register_global_callable(
+        js_string!("compileRecord"),
+        1,
+        NativeFunction::from_fn_ptr(compile_record),
+    )?;
+
+    let import = context
+        .eval(Source::from_bytes("createCompartment"))?
+        .as_callable()
+        .unwrap()
+        .call(
+            &JsValue::undefined(),
+            &[
+                js_string!(root.display().to_string()).into(),
+                context.eval(Source::from_bytes("compileRecord"))?,
+            ],
+            context,
+        )?;
+    let entry = js_string!(entry.file_name().unwrap().to_string_lossy().to_string());
+    let promise = JsPromise::from_object(
+        import
+            .as_callable()
+            .unwrap()
+            .call(&JsValue::undefined(), &[entry.into()], context)?
+            .as_object()
+            .unwrap()
+            .clone(),
+    )?;
+    context.run_jobs();
+    Ok(promise.state())
+}
+
+fn report(context: &mut Context, state: PromiseState, namespace: JsValue) -> JsResult<String> {
+    let error = match state {
+        PromiseState::Pending => {
+            return Err(JsNativeError::error()
+                .with_message("module evaluation never settled")
+                .into())
+        }
+        PromiseState::Fulfilled(_) => JsValue::undefined(),
+        PromiseState::Rejected(error) => error,
+    };
+    let result = context
+        .eval(Source::from_bytes("report"))?
+        .as_callable()
+        .unwrap()
+        .call(&JsValue::undefined(), &[namespace, error], context)?;
+    Ok(result.to_string(context)?.to_std_string_escaped())
+}
+
+/// `compileRecord(path)` exposed to the compartment, returns the code of the record.
+///
+/// Code that fails to compile throws a SyntaxError, like the parse phase of a native module.
+fn compile_record(_: &JsValue, args: &[JsValue], context: &mut Context) -> JsResult<JsValue> {
+    let path = args[0].to_string(context)?.to_std_string_escaped();
+    let path = PathBuf::from(path);
+    let source = read_to_string(&path).map_err(io_error)?;
+    let config = Config {
+        template: Template::Eval,
+        ..parse_config(&source).unwrap_or_default()
+    };
+    let code = catch_unwind(|| transform(&path, &source, config)).map_err(|_| {
+        JsNativeError::syntax().with_message(format!("failed to compile {}", path.display()))
+    })?;
+    Ok(js_string!(code).into())
+}
+
+fn io_error(err: std::io::Error) -> JsError {
+    JsNativeError::error().with_message(err.to_string()).into()
+}
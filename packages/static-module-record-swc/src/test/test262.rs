@@ -0,0 +1,267 @@
+use std::collections::BTreeSet;
+use std::env;
+use std::fs::{read_dir, read_to_string, write};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use boa_engine::builtins::promise::PromiseState;
+use boa_engine::{Context, JsNativeError, JsResult, JsValue, Source};
+
+use super::semantics::import_module;
+
+const CHECKOUT: &str = "tests/test262/test262";
+const EXPECTED_FAILURES: &str = "tests/test262/expected-failures.txt";
+const REPORT: &str = "target/test262-report.txt";
+/// The header line of the expected failures with the commit of the checkout.
+const GENERATED_FROM: &str = "# Generated from test262";
+
+/// Compile every test in `test/language/module-code` of a Test262 checkout into records and run
+/// them with the compartment of the semantic tests.
+///
+/// The checkout is read from `$TEST262` (default `tests/test262/test262`). Known failures are
+/// listed in `tests/test262/expected-failures.txt`, any difference to that list fails the test.
+/// Set `TEST262_UPDATE=1` to rewrite the list from the current results, the list records the
+/// commit of the checkout it was generated from. Tests without the `module` flag are skipped.
+/// The full report is written to `target/test262-report.txt`.
+#[test]
+#[ignore = "requires a Test262 checkout"]
+fn test262() {
+    let checkout = env::var("TEST262")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from(CHECKOUT));
+    assert!(
+        checkout.join("harness").exists(),
+        "Test262 checkout not found at {}",
+        checkout.display()
+    );
+
+    let mut tests = vec![];
+    collect_tests(&checkout.join("test/language/module-code"), &mut tests);
+    tests.sort();
+
+    let mut report = vec![];
+    let mut failures = BTreeSet::new();
+    let mut skipped = 0;
+    for test in &tests {
+        let name = test
+            .strip_prefix(checkout.join("test"))
+            .unwrap()
+            .display()
+            .to_string()
+            .replace('\\', "/");
+        match run_test(&checkout, test) {
+            Ok(Outcome::Pass) => report.push(format!("PASS {}", name)),
+            Ok(Outcome::Skip(reason)) => {
+                report.push(format!("SKIP {}: {}", name, reason));
+                skipped += 1;
+            }
+            Err(reason) => {
+                report.push(format!("FAIL {}: {}", name, reason));
+                failures.insert(name);
+            }
+        }
+    }
+    report.push(format!(
+        "\n{} passed, {} failed, {} skipped, {} total",
+        tests.len() - failures.len() - skipped,
+        failures.len(),
+        skipped,
+        tests.len()
+    ));
+    write(REPORT, report.join("\n")).unwrap();
+
+    if env::var("TEST262_UPDATE").is_ok() {
+        let mut list = read_to_string(EXPECTED_FAILURES)
+            .unwrap_or_default()
+            .lines()
+            .take_while(|line| line.starts_with('#') && !line.starts_with(GENERATED_FROM))
+            .map(|line| format!("{}\n", line))
+            .collect::<String>();
+        list.push_str(&format!("{} {}\n", GENERATED_FROM, revision(&checkout)));
+        list.extend(failures.iter().map(|name| format!("{}\n", name)));
+        write(EXPECTED_FAILURES, list).unwrap();
+        return;
+    }
+
+    let list = read_to_string(EXPECTED_FAILURES).unwrap_or_default();
+    assert!(
+        list.lines().any(|line| line.starts_with(GENERATED_FROM)),
+        "{} was never generated, run with TEST262_UPDATE=1",
+        EXPECTED_FAILURES
+    );
+    let expected = list
+        .lines()
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(String::from)
+        .collect::<BTreeSet<_>>();
+    let unexpected_failures = failures.difference(&expected).collect::<Vec<_>>();
+    let unexpected_passes = expected.difference(&failures).collect::<Vec<_>>();
+    assert!(
+        unexpected_failures.is_empty() && unexpected_passes.is_empty(),
+        "unexpected failures: {:#?}\nunexpected passes: {:#?}\nsee {}",
+        unexpected_failures,
+        unexpected_passes,
+        REPORT
+    );
+}
+
+/// The commit of the checkout, if it is a git repository.
+fn revision(checkout: &Path) -> String {
+    Command::new("git")
+        .arg("-C")
+        .arg(checkout)
+        .args(["rev-parse", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .unwrap_or_else(|| "an unknown commit".to_string())
+}
+
+fn collect_tests(dir: &Path, tests: &mut Vec<PathBuf>) {
+    for entry in read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            collect_tests(&path, tests);
+        } else if path.extension().is_some_and(|ext| ext == "js")
+            && !path.to_string_lossy().ends_with("_FIXTURE.js")
+        {
+            tests.push(path);
+        }
+    }
+}
+
+/// The parts of the YAML front matter of a test that the runner understands.
+#[derive(Default)]
+struct Metadata {
+    flags: Vec<String>,
+    includes: Vec<String>,
+    negative: Option<(String, String)>,
+}
+
+impl Metadata {
+    fn parse(source: &str) -> Metadata {
+        let mut metadata = Metadata::default();
+        let (Some(start), Some(end)) = (source.find("/*---"), source.find("---*/")) else {
+            return metadata;
+        };
+        let mut key = "";
+        let (mut phase, mut error_type) = (None, None);
+        for line in source[start + 5..end].lines() {
+            let trimmed = line.trim();
+            if !line.starts_with(' ') {
+                if let Some((k, value)) = trimmed.split_once(':') {
+                    key = k.trim();
+                    if let Some(list) = value.trim().strip_prefix('[') {
+                        let list = list.trim_end_matches(']').split(',');
+                        let list = list.map(|x| x.trim().to_string()).filter(|x| !x.is_empty());
+                        match key {
+                            "flags" => metadata.flags.extend(list),
+                            "includes" => metadata.includes.extend(list),
+                            _ => {}
+                        }
+                    }
+                }
+            } else if let Some(item) = trimmed.strip_prefix("- ") {
+                match key {
+                    "flags" => metadata.flags.push(item.trim().to_string()),
+                    "includes" => metadata.includes.push(item.trim().to_string()),
+                    _ => {}
+                }
+            } else if key == "negative" {
+                match trimmed.split_once(':') {
+                    Some(("phase", value)) => phase = Some(value.trim().to_string()),
+                    Some(("type", value)) => error_type = Some(value.trim().to_string()),
+                    _ => {}
+                }
+            }
+        }
+        metadata.negative = phase.zip(error_type);
+        metadata
+    }
+}
+
+enum Outcome {
+    Pass,
+    /// Not run, with the reason.
+    Skip(&'static str),
+}
+
+fn run_test(checkout: &Path, test: &Path) -> Result<Outcome, String> {
+    let metadata = Metadata::parse(&read_to_string(test).map_err(|err| err.to_string())?);
+    if !metadata.flags.iter().any(|flag| flag == "module") {
+        return Ok(Outcome::Skip("not a module test"));
+    }
+    catch_unwind(AssertUnwindSafe(|| {
+        evaluate(checkout, test, &metadata).map_err(|err| err.to_string())?
+    }))
+    .unwrap_or_else(|_| Err("panicked".to_string()))
+    .map(|()| Outcome::Pass)
+}
+
+fn evaluate(checkout: &Path, test: &Path, metadata: &Metadata) -> JsResult<Result<(), String>> {
+    let context = &mut Context::default();
+    context.eval(Source::from_bytes(
+        "var printed = []; var print = (message) => printed.push(String(message));",
+    ))?;
+    let mut includes = vec!["assert.js", "sta.js"];
+    if metadata.flags.iter().any(|flag| flag == "async") {
+        includes.push("doneprintHandle.js");
+    }
+    includes.extend(metadata.includes.iter().map(String::as_str));
+    for include in includes {
+        let path = checkout.join("harness").join(include);
+        context.eval(Source::from_filepath(&path).map_err(|err| {
+            JsNativeError::error().with_message(format!("{}: {}", include, err))
+        })?)?;
+    }
+
+    let state = import_module(context, test)?;
+    let printed = context
+        .eval(Source::from_bytes("printed.join('\\n')"))?
+        .to_string(context)?
+        .to_std_string_escaped();
+    Ok(match (state, &metadata.negative) {
+        (PromiseState::Pending, _) => Err("never settled".to_string()),
+        (PromiseState::Fulfilled(_), None) => {
+            if metadata.flags.iter().any(|flag| flag == "async")
+                && !printed.contains("Test262:AsyncTestComplete")
+            {
+                Err(format!("async test did not complete: {}", printed))
+            } else {
+                Ok(())
+            }
+        }
+        (PromiseState::Fulfilled(_), Some((phase, error_type))) => Err(format!(
+            "expected a {} in the {} phase, but it succeeded",
+            error_type, phase
+        )),
+        (PromiseState::Rejected(error), None) => Err(describe(context, error)?),
+        (PromiseState::Rejected(error), Some((_, error_type))) => {
+            let actual = error_name(context, &error)?;
+            if &actual == error_type {
+                Ok(())
+            } else {
+                Err(format!(
+                    "expected {} but got {}",
+                    error_type,
+                    describe(context, error)?
+                ))
+            }
+        }
+    })
+}
+
+fn error_name(context: &mut Context, error: &JsValue) -> JsResult<String> {
+    let name = context
+        .eval(Source::from_bytes("(error) => error?.constructor?.name"))?
+        .as_callable()
+        .unwrap()
+        .call(&JsValue::undefined(), std::slice::from_ref(error), context)?;
+    Ok(name.to_string(context)?.to_std_string_escaped())
+}
+
+fn describe(context: &mut Context, error: JsValue) -> JsResult<String> {
+    Ok(error.to_string(context)?.to_std_string_escaped())
+}
@@ -26,6 +26,21 @@ pub fn emit_error(span: Span, msg: &str) {
     errors::HANDLER.with(|reporter| reporter.emit(&m_span, msg, errors::Level::Error));
 }
 
+pub fn emit_warning(span: Span, msg: &str) {
+    let mut m_span = MultiSpan::new();
+    m_span.push_span_label(span, "here".into());
+    errors::HANDLER.with(|reporter| reporter.emit(&m_span, msg, errors::Level::Warning));
+}
+
+/// Emit a diagnostic at `span` with notes at `labels`, which may be in other files.
+pub fn emit_with_labels(level: errors::Level, span: Span, msg: &str, labels: &[(Span, String)]) {
+    let mut m_span = MultiSpan::from_span(span);
+    for (span, label) in labels {
+        m_span.push_span_label(*span, label.clone());
+    }
+    errors::HANDLER.with(|reporter| reporter.emit(&m_span, msg, level));
+}
+
 pub fn relative(file_name: &String, base: &String) -> String {
     if !file_name.starts_with(base) {
         panic!("file_name {} should starts with cwd {}", file_name, base);
@@ -39,3 +54,26 @@ pub fn relative(file_name: &String, base: &String) -> String {
         format!("/{}", uri)
     }
 }
+
+/// The 32-bit FNV-1a hash of `bytes`.
+pub fn fnv1a(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0x811c9dc5, |hash, byte| {
+        (hash ^ u32::from(*byte)).wrapping_mul(0x01000193)
+    })
+}
+
+/// A JavaScript string literal of `value`.
+pub fn js_string(value: &str) -> String {
+    serde_json::to_string(value).unwrap()
+}
+
+/// A property name of `value` in a JavaScript object literal.
+///
+/// `{ "__proto__": x }` sets the prototype instead of defining a property.
+pub fn js_key(value: &str) -> String {
+    if value == "__proto__" {
+        format!("[{}]", js_string(value))
+    } else {
+        js_string(value)
+    }
+}
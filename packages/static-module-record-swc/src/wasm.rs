@@ -0,0 +1,342 @@
+use std::fmt::Write;
+
+use crate::utils::{js_key, js_string};
+
+/// An import of a WebAssembly module, `(import "module" "name" ...)`.
+pub struct WasmImport {
+    pub module: String,
+    pub name: String,
+}
+
+/// An export of a WebAssembly module, `(export "name" ...)`.
+pub struct WasmExport {
+    pub name: String,
+    pub kind: WasmExportKind,
+}
+
+pub enum WasmExportKind {
+    Function,
+    /// A global, exported as its value rather than the `WebAssembly.Global`.
+    Global {
+        mutable: bool,
+    },
+    /// A table, memory or tag.
+    Other,
+}
+
+/// The imports and exports of a WebAssembly module, in the order of their sections.
+pub struct WasmInterface {
+    pub imports: Vec<WasmImport>,
+    pub exports: Vec<WasmExport>,
+}
+
+/// Read the import, global and export sections of a WebAssembly binary.
+///
+/// Other sections are skipped without validation, `WebAssembly.instantiate` validates the whole
+/// module when the record is executed. The global section is only read for the mutability of
+/// exported globals.
+pub fn parse_wasm(bytes: &[u8]) -> Result<WasmInterface, String> {
+    let mut reader = Reader { bytes, pos: 0 };
+    if reader.take(4)? != b"\0asm" {
+        return Err("not a WebAssembly binary".to_string());
+    }
+    if reader.take(4)? != [1, 0, 0, 0] {
+        return Err("unsupported WebAssembly binary version".to_string());
+    }
+    let mut interface = WasmInterface {
+        imports: vec![],
+        exports: vec![],
+    };
+    // The mutability of each global, imported globals come first in the index space.
+    let mut globals = vec![];
+    let mut exports = vec![];
+    while reader.pos < bytes.len() {
+        let id = reader.byte()?;
+        let size = reader.u32()? as usize;
+        let mut section = Reader {
+            bytes: reader.take(size)?,
+            pos: 0,
+        };
+        match id {
+            2 => {
+                for _ in 0..section.u32()? {
+                    let module = section.name()?;
+                    let name = section.name()?;
+                    if let Some(mutable) = section.import_desc()? {
+                        globals.push(mutable);
+                    }
+                    interface.imports.push(WasmImport { module, name });
+                }
+            }
+            6 => {
+                for _ in 0..section.u32()? {
+                    globals.push(section.global_type()?);
+                    section.const_expr()?;
+                }
+            }
+            7 => {
+                for _ in 0..section.u32()? {
+                    let name = section.name()?;
+                    let kind = section.byte()?;
+                    let index = section.u32()? as usize;
+                    exports.push((name, kind, index));
+                }
+            }
+            _ => {}
+        }
+    }
+    for (name, kind, index) in exports {
+        let kind = match kind {
+            0 => WasmExportKind::Function,
+            3 => WasmExportKind::Global {
+                mutable: *globals
+                    .get(index)
+                    .ok_or("unknown global index in the WebAssembly binary")?,
+            },
+            _ => WasmExportKind::Other,
+        };
+        interface.exports.push(WasmExport { name, kind });
+    }
+    Ok(interface)
+}
+
+/// Generate an ES module that instantiates `bytes` like the WebAssembly/ES module integration.
+///
+/// ```js
+/// import { "name" as import0 } from "module"
+/// const { instance } = await WebAssembly.instantiate(bytes, { "module": { "name": import0 } })
+/// const export0 = instance.exports["name"]
+/// export { export0 as "name" }
+/// ```
+///
+/// Exported globals are their `value` instead of the `WebAssembly.Global`. JavaScript only runs
+/// the instance through its exported functions, so when a global is mutable the functions are
+/// wrapped to update the exported values after each call:
+///
+/// ```js
+/// const update = () => { export1 = instance.exports["counter"].value }
+/// const wrap = (f) => Object.defineProperty({ [f.name](...args) { ... } }[f.name], "length", ...)
+/// const export0 = wrap(instance.exports["increment"])
+/// let export1 = instance.exports["counter"].value
+/// ```
+pub fn wasm_to_module(bytes: &[u8], interface: &WasmInterface) -> String {
+    let mut code = String::new();
+    let mut import_object: Vec<(&str, Vec<(&str, usize)>)> = vec![];
+    for (index, import) in interface.imports.iter().enumerate() {
+        writeln!(
+            code,
+            "import {{ {} as import{} }} from {};",
+            js_string(&import.name),
+            index,
+            js_string(&import.module)
+        )
+        .unwrap();
+        let field = (import.name.as_str(), index);
+        match import_object
+            .iter_mut()
+            .find(|(module, _)| *module == import.module)
+        {
+            Some((_, fields)) => fields.push(field),
+            None => import_object.push((&import.module, vec![field])),
+        }
+    }
+    write!(
+        code,
+        "const {{ instance }} = await WebAssembly.instantiate(Uint8Array.from({}, (c) => c.charCodeAt(0)), {{",
+        byte_string(bytes)
+    )
+    .unwrap();
+    for (module, fields) in import_object {
+        write!(code, " {}: {{", js_key(module)).unwrap();
+        for (name, index) in fields {
+            write!(code, " {}: import{},", js_key(name), index).unwrap();
+        }
+        code.push_str(" },");
+    }
+    code.push_str(" });\n");
+    let mutable_globals: Vec<_> = (interface.exports.iter().enumerate())
+        .filter(|(_, export)| matches!(export.kind, WasmExportKind::Global { mutable: true }))
+        .collect();
+    if !mutable_globals.is_empty() {
+        code.push_str("const update = () => {");
+        for (index, export) in &mutable_globals {
+            write!(
+                code,
+                " export{} = instance.exports[{}].value;",
+                index,
+                js_string(&export.name)
+            )
+            .unwrap();
+        }
+        code.push_str(" };\n");
+        code.push_str(concat!(
+            "const wrap = (f) => Object.defineProperty({ [f.name](...args) { try { return f(...args); } finally { update(); } } }[f.name], ",
+            "\"length\", { value: f.length });\n"
+        ));
+    }
+    for (index, export) in interface.exports.iter().enumerate() {
+        let name = js_string(&export.name);
+        let (declaration, value) = match export.kind {
+            WasmExportKind::Function if !mutable_globals.is_empty() => {
+                ("const", format!("wrap(instance.exports[{}])", name))
+            }
+            WasmExportKind::Global { mutable } => (
+                if mutable { "let" } else { "const" },
+                format!("instance.exports[{}].value", name),
+            ),
+            _ => ("const", format!("instance.exports[{}]", name)),
+        };
+        writeln!(
+            code,
+            "{} export{} = {};\nexport {{ export{} as {} }};",
+            declaration, index, value, index, name
+        )
+        .unwrap();
+    }
+    code
+}
+
+/// One char per byte, printable ASCII is kept as is and everything else is `\xHH`.
+fn byte_string(bytes: &[u8]) -> String {
+    let mut result = String::with_capacity(bytes.len() * 2 + 2);
+    result.push('"');
+    for byte in bytes {
+        match byte {
+            b'"' | b'\\' => write!(result, "\\{}", char::from(*byte)).unwrap(),
+            0x20..=0x7e => result.push(char::from(*byte)),
+            _ => write!(result, "\\x{:02x}", byte).unwrap(),
+        }
+    }
+    result.push('"');
+    result
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or("unexpected end of the WebAssembly binary")?;
+        let bytes = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+    fn byte(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+    fn leb128(&mut self, bits: u32) -> Result<u64, String> {
+        let mut result = 0;
+        let mut shift = 0;
+        loop {
+            let byte = self.byte()?;
+            if shift >= bits {
+                return Err("integer too large in the WebAssembly binary".to_string());
+            }
+            result |= u64::from(byte & 0x7f) << shift;
+            shift += 7;
+            if byte & 0x80 == 0 {
+                return Ok(result);
+            }
+        }
+    }
+    fn u32(&mut self) -> Result<u32, String> {
+        Ok(self.leb128(32)? as u32)
+    }
+    fn name(&mut self) -> Result<String, String> {
+        let len = self.u32()? as usize;
+        String::from_utf8(self.take(len)?.to_vec())
+            .map_err(|_| "malformed UTF-8 name in the WebAssembly binary".to_string())
+    }
+    fn limits(&mut self) -> Result<(), String> {
+        let flags = self.byte()?;
+        // bit 2 is memory64, where the limits are u64.
+        let bits = if flags & 4 == 0 { 32 } else { 64 };
+        self.leb128(bits)?;
+        if flags & 1 != 0 {
+            self.leb128(bits)?;
+        }
+        Ok(())
+    }
+    fn value_type(&mut self) -> Result<(), String> {
+        match self.byte()? {
+            // `(ref null ht)` and `(ref ht)` of the GC proposal, followed by the heap type.
+            0x63 | 0x64 => self.leb128(33).map(drop),
+            _ => Ok(()),
+        }
+    }
+    /// The mutability of a global is returned, other imports are skipped.
+    fn import_desc(&mut self) -> Result<Option<bool>, String> {
+        match self.byte()? {
+            // function: type index
+            0 => self.u32().map(|_| None),
+            // table: reference type and limits
+            1 => {
+                self.value_type()?;
+                self.limits().map(|_| None)
+            }
+            // memory: limits
+            2 => self.limits().map(|_| None),
+            3 => self.global_type().map(Some),
+            // tag: attribute and type index
+            4 => {
+                self.byte()?;
+                self.u32().map(|_| None)
+            }
+            kind => Err(format!(
+                "unknown import kind {} in the WebAssembly binary",
+                kind
+            )),
+        }
+    }
+    /// Value type and mutability, returns whether the global is mutable.
+    fn global_type(&mut self) -> Result<bool, String> {
+        self.value_type()?;
+        Ok(self.byte()? == 1)
+    }
+    /// Skip the initializer of a global, up to its `end`.
+    fn const_expr(&mut self) -> Result<(), String> {
+        loop {
+            match self.byte()? {
+                // end
+                0x0b => return Ok(()),
+                // i32.const
+                0x41 => self.leb128(32).map(drop)?,
+                // i64.const
+                0x42 => self.leb128(64).map(drop)?,
+                // f32.const and f64.const
+                0x43 => self.take(4).map(drop)?,
+                0x44 => self.take(8).map(drop)?,
+                // global.get and ref.func
+                0x23 | 0xd2 => self.u32().map(drop)?,
+                // ref.null: heap type
+                0xd0 => self.leb128(33).map(drop)?,
+                // extended constant expressions: i32 and i64 add, sub and mul
+                0x6a..=0x6c | 0x7c..=0x7e => {}
+                // v128.const
+                0xfd if self.u32()? == 12 => self.take(16).map(drop)?,
+                // GC: array.new_fixed has a type index and a length, any.convert_extern,
+                // extern.convert_any and ref.i31 have none, the others a type index.
+                0xfb => match self.u32()? {
+                    8 => {
+                        self.u32()?;
+                        self.u32()?;
+                    }
+                    26..=28 => {}
+                    _ => self.u32().map(drop)?,
+                },
+                opcode => {
+                    return Err(format!(
+                        "unsupported instruction 0x{:02x} in a constant expression of the WebAssembly binary",
+                        opcode
+                    ))
+                }
+            }
+        }
+    }
+}
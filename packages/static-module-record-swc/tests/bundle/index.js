@@ -0,0 +1,7 @@
+import { greet } from './lib/greet.js'
+import data from './data.json'
+import { readFile } from 'node:fs/promises'
+
+export * from './lib/greet.js'
+export const message = greet(data.name)
+export const lazy = () => import('./lib/lazy.js')
@@ -0,0 +1 @@
+export default 'loaded on demand'
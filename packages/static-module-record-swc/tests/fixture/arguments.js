@@ -24,3 +24,13 @@
         const x = { arguments }
     }
 }
+
+// no transform
+function outer() {
+    return class extends arguments[0] {
+        [arguments[1]] = 1
+        constructor() {
+            super(arguments)
+        }
+    }
+}
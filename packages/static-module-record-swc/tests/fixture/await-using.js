@@ -0,0 +1,5 @@
+import { connect } from 'db'
+
+await using connection = connect()
+using cursor = connection.cursor()
+export let rows = cursor.fetch()
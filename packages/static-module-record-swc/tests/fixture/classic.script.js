@@ -0,0 +1,12 @@
+var counter = 0, { name } = { name: 'classic' }, unset
+function increment() {
+    return ++counter
+}
+let scoped = increment()
+for (var i = 0; i < 2; i++) increment()
+for (var key in { a: 1 }) console.log(key)
+if (counter) {
+    var nested = this
+}
+implicit = scoped
+delete unset
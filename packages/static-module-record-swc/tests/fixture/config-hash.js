@@ -0,0 +1,2 @@
+/// {"hash": true}
+export const a = 1
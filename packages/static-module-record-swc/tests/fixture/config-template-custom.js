@@ -0,0 +1,2 @@
+/// {"template":{"type":"custom","template":"\"use strict\";\nglobalThis.register($SPECIFIER, $HASH, $RECORD);","cwd":""}}
+export const a = 1
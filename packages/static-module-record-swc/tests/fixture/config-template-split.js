@@ -0,0 +1,3 @@
+/// {"template":{"type":"split","callback":"__registerExecute","cwd":""}}
+import { a } from './a.js'
+export const b = a + 1
@@ -0,0 +1,6 @@
+const local = 1
+eval('local')
+{
+    const eval = (code) => code
+    eval('not a direct eval')
+}
@@ -0,0 +1,17 @@
+/// {"directEval": true}
+import { imported } from 'mod'
+export let counter = 0
+const constant = 1
+function outer(param) {
+    let inner = param
+    {
+        const shadowed = 2
+        return eval('inner + shadowed + constant + counter++ + imported')
+    }
+}
+export const f = function named() {
+    var hoisted
+    return eval(arguments[0])
+}
+outer(1)
+export const g = { get value() { return eval('arguments') } }
@@ -0,0 +1,5 @@
+export { x, x as y, z as default }
+export { x as w } from 'other'
+
+import { x } from 'mod'
+import * as z from 'mod2'
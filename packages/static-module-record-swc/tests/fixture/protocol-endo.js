@@ -0,0 +1,14 @@
+/// {"protocol":"endo"}
+import a, { b as c } from 'mod'
+import * as ns from 'mod2'
+export { d, e as "e-f" } from 'mod3'
+export * from 'mod4'
+export * as all from 'mod5'
+
+export const fixed = a + c
+export let live = 0
+export function update() {
+    live++
+}
+export default class {}
+console.log(ns, import.meta.url)
@@ -0,0 +1,4 @@
+/// {"protocol":"module-source"}
+import { a } from 'mod'
+export const b = a
+await import(import.meta.url)
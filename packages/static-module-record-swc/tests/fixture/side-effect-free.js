@@ -0,0 +1,14 @@
+import { createContext, Base } from 'lib'
+
+export const Context = /*#__PURE__*/ createContext({ value: null })
+export const config = { name: 'config', items: [1, 2, 3], get size() { return 3 } }
+export const enabled = typeof config === 'object' && !Context
+
+export function helper() {
+    return sideEffect()
+}
+export class Component extends Base {
+    static displayName = 'Component'
+    render() {}
+}
+export default () => helper()
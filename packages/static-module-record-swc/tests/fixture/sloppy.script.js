@@ -0,0 +1,26 @@
+function reserved(static, a, a) {
+    var arguments = [static]
+    function yield() {}
+    return [arguments, a, yield]
+}
+function linked(a) {
+    a = 1
+    return arguments[0]
+}
+function blocks() {
+    {
+        function inner() {}
+    }
+    return inner
+}
+{
+    function global() {}
+}
+function callee() {
+    return arguments.callee
+}
+undefined = 1
+with (object) {
+    var x = y
+    f(x)
+}
@@ -0,0 +1,31 @@
+export const self = this
+const arrow = () => this?.value
+if (typeof this === 'undefined') arrow()
+export function method() {
+    return this
+}
+export class Class {
+    field = this
+    static {
+        this.static = true
+    }
+}
+export const object = {
+    get getter() {
+        return this
+    },
+    set setter(value) {
+        this.value = value
+    },
+    method() {
+        return this
+    },
+}
+this.member
+// `extends` and computed keys are evaluated in the outer scope.
+export class Derived extends (this ?? Object) {
+    [this?.key] = this;
+    static [this?.key]() {
+        return this
+    }
+}
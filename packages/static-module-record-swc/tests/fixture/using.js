@@ -0,0 +1,12 @@
+import { open } from 'fs'
+
+using file = open('a.txt'), lock = null
+export { file }
+
+{
+    using scoped = open('b.txt')
+}
+
+export function read() {
+    return file.read()
+}
@@ -0,0 +1,2 @@
+export const shared = 1
+export const onlyB = 1
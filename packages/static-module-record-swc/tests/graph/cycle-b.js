@@ -0,0 +1,2 @@
+import { onlyB } from './b.js'
+import './cycle-a.js'
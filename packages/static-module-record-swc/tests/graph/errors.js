@@ -0,0 +1,3 @@
+import { missing } from './a.js'
+import { shared } from './stars.js'
+export { present as renamed, absent } from './a.js'
@@ -0,0 +1,7 @@
+import { shared as local, onlyB } from './b.js'
+import * as ns from './a.js'
+import { renamed } from './reexport.js'
+import { readFile } from 'node:fs/promises'
+
+export * from './stars.js'
+export { present as again } from './a.js'
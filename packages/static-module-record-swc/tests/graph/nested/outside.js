@@ -0,0 +1,2 @@
+import { present } from '../a.js'
+export { present }
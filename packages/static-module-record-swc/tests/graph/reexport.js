@@ -0,0 +1 @@
+export { present as renamed } from './a.js'
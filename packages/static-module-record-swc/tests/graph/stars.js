@@ -0,0 +1,2 @@
+export * from './b.js'
+export * from './c.js'
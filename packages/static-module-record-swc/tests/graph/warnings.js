@@ -0,0 +1,3 @@
+export * from './b.js'
+export * from './c.js'
+import './cycle-a.js'
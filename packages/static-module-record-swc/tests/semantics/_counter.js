@@ -0,0 +1,5 @@
+log.push('counter')
+export let count = 0
+export function increment() {
+    count++
+}
@@ -0,0 +1,5 @@
+export default class {
+    static name() {
+        return 'static'
+    }
+}
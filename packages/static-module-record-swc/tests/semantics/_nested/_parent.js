@@ -0,0 +1 @@
+export { count as fromParent } from '../_counter.js'
@@ -0,0 +1,34 @@
+// A classic script, run natively as a script and as a record executed with the real globalThis.
+var existing = 'overwritten', declared, { destructured } = { destructured: 'value' }
+function hoisted() {
+    return typeof later
+}
+log.push(hoisted(), typeof declared, existing)
+var later = 1
+let lexical = 'lexical'
+for (var i = 0; i < 2; i++) log.push(i)
+if (this === globalThis) {
+    var nested = 'nested'
+}
+implicit = lexical
+function plainThis() {
+    return this === globalThis
+}
+function boxedThis() {
+    var arrow = () => this
+    return [typeof this, arrow() === this]
+}
+var sloppy = [
+    plainThis(),
+    boxedThis.call(1),
+    010,
+    '\101',
+    (function () {
+        var local
+        return delete local
+    })(),
+    { get self() { return this } }.self === globalThis,
+]
+var undeletable
+deletable = 1
+sloppy.push(delete undeletable, delete deletable, typeof deletable)
@@ -0,0 +1,3 @@
+log.push('a')
+export const a = 'a'
+export default 'default of a'
@@ -0,0 +1,3 @@
+import './_side-effect-a.js'
+log.push('b')
+export const b = 'b'
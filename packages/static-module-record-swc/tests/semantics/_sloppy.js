@@ -0,0 +1,69 @@
+// Sloppy mode code, run natively as a script and as a record executed with the real globalThis.
+var result = []
+
+// Strict mode reserved words, `eval` and `arguments` as local names.
+function reserved(static, eval) {
+    var let = static + 1, { implements } = { implements: eval }
+    function yield() {}
+    var named = function interface() { return typeof interface }
+    package: for (;;) break package
+    return [let, implements, yield.name, named(), named.name, { static }]
+}
+result.push(reserved(1, 2))
+
+// Duplicate parameters, the last one is the binding.
+function duplicate(a, a) {
+    return a
+}
+result.push(duplicate(1, 2))
+
+// `arguments` linked to the parameters.
+function linked(a, b) {
+    a = 'assigned'
+    arguments[1] = 'written'
+    return [arguments[0], b, arguments.length]
+}
+result.push(linked(1, 2), linked(1))
+function assignedArguments() {
+    arguments = 'arguments'
+    return arguments
+}
+result.push(assignedArguments())
+
+// `arguments.callee`
+function callee(n) {
+    return n ? arguments.callee(n - 1) + 1 : 0
+}
+var reassigned = callee
+callee = null
+result.push(reassigned(3), (function named() { return arguments.callee === named })())
+
+// Direct eval at the top level, and `this` in parameters.
+eval('var evaluated = "evaluated"')
+function thisParam(self = this) {
+    return typeof self
+}
+result.push(evaluated, thisParam.call(1))
+
+// Not writable globals.
+undefined = 1
+NaN++
+result.push(typeof undefined, NaN !== NaN)
+
+// `this` in the heritage and the computed keys of classes.
+function Base() {}
+globalThis.key = 'key'
+class Derived extends (this === globalThis ? Base : Object) {
+    [this.key]() {}
+}
+result.push(Object.getPrototypeOf(Derived) === Base, typeof Derived.prototype.key)
+
+// with
+var scope = { x: 'scope', f() { return 'f' }, [Symbol.unscopables]: { hidden: true }, hidden: 'scope' }
+var x = 'global', hidden = 'global', y = 'global'
+with (scope) {
+    var y = x
+    x = 'written'
+    result.push(f(), hidden, typeof z)
+}
+result.push(scope.x, x, y)
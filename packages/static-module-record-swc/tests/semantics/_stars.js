@@ -0,0 +1,2 @@
+export * from './_star-a.js'
+export * from './_star-b.js'
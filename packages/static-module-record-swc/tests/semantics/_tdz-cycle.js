@@ -0,0 +1,8 @@
+import { value } from './tdz.js'
+let early
+try {
+    early = value
+} catch (error) {
+    early = error.name
+}
+export { early };
@@ -0,0 +1,25 @@
+// Only run as a record, like _using.js.
+Symbol.dispose ??= Symbol('Symbol.dispose')
+
+function resource(name) {
+    return {
+        [Symbol.dispose]() {
+            log.push(`dispose ${name}`)
+        },
+    }
+}
+
+using a = resource('a')
+{
+    using b = resource('b')
+    log.push('block')
+}
+function inner() {
+    using c = resource('c')
+    log.push('function')
+    return 'returned'
+}
+log.push(inner())
+
+throw new Error('body')
+export function hoisted() {}
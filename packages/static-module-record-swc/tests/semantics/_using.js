@@ -0,0 +1,28 @@
+// boa does not support explicit resource management, this module is only run as a record.
+Symbol.dispose ??= Symbol('Symbol.dispose')
+Symbol.asyncDispose ??= Symbol('Symbol.asyncDispose')
+
+function resource(name) {
+    return {
+        [Symbol.dispose]() {
+            log.push(`dispose ${name}`)
+            throw new Error(name)
+        },
+    }
+}
+function asyncResource(name) {
+    return {
+        async [Symbol.asyncDispose]() {
+            await null
+            log.push(`async dispose ${name}`)
+        },
+    }
+}
+
+using a = resource('a')
+await using b = asyncResource('b'), nothing = null
+using c = resource('c')
+export { a }
+
+log.push('body')
+throw new Error('body')
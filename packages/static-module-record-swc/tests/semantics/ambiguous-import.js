@@ -0,0 +1,2 @@
+import { shared } from './_stars.js'
+export { shared };
@@ -0,0 +1,4 @@
+// `shared` is provided by both `export *`, so it is not an export of `_stars.js`.
+import * as stars from './_stars.js'
+export const names = Object.keys(stars).join()
+export const hasShared = 'shared' in stars
@@ -0,0 +1,7 @@
+// Anonymous default exports are named `default`, unless a class defines a static `name`.
+import fn from './_default-fn.js'
+import Class from './_default-class.js'
+import arrow from './_default-arrow.js'
+import StaticName from './_default-static-name.js'
+
+export const names = [fn.name, Class.name, arrow.name, typeof StaticName.name, Class.id]
@@ -0,0 +1,24 @@
+/// {"directEval": true}
+import { count, increment } from './_counter.js'
+export let exported = 'before'
+const constant = 'constant'
+function read(param) {
+    const local = 'local'
+    return eval('[param, local, constant, count, typeof increment, exported].join()')
+}
+export const result = read('param')
+eval('exported = "after"')
+export let assignConst
+try {
+    eval('constant = 1')
+} catch (error) {
+    assignConst = error.name
+}
+export const declared = eval('var scoped = 1; typeof scoped') + typeof scoped
+export const args = (function () {
+    return eval('arguments.length + arguments[0]')
+})(1, 2)
+export const arrowArgs = (function () {
+    return (() => eval('arguments[0]'))()
+})('outer')
+export const getterArgs = { get g() { return eval('typeof arguments') } }.g
@@ -0,0 +1,5 @@
+log.push('entry')
+const { count, increment } = await import('./_counter.js')
+increment()
+export const before = count
+export const after = (await import('./_counter.js')).count
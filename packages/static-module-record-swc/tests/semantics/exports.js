@@ -0,0 +1,7 @@
+export let x = 1
+export const [first, { second = 2 }, ...rest] = [1, {}, 3, 4]
+export function f() {}
+export class C {}
+var local = 'local'
+export { local as renamed, local as 'string name' }
+export default x + 1
@@ -0,0 +1,11 @@
+import { count, increment } from './_counter.js'
+
+log.push(count)
+increment()
+log.push(count)
+
+export { count }
+export function run() {
+    increment()
+    log.push(count)
+}
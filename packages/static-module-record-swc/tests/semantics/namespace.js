@@ -0,0 +1,6 @@
+import * as counter from './_counter.js'
+
+counter.increment()
+log.push(counter.count)
+export { counter }
+export const ns = counter
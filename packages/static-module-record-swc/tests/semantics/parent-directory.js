@@ -0,0 +1,2 @@
+import { fromParent } from './_nested/_parent.js'
+export { fromParent };
@@ -0,0 +1,6 @@
+import { b } from './_side-effect-b.js'
+import './_side-effect-a.js'
+
+log.push('entry', b)
+export * from './_side-effect-a.js'
+export * as b from './_side-effect-b.js'
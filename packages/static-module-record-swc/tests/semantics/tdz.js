@@ -0,0 +1,4 @@
+// The cycle reads `value` before this module initializes it.
+import { early } from './_tdz-cycle.js'
+export let value = 'initialized'
+export const observed = early
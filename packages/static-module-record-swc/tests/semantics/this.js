@@ -0,0 +1,33 @@
+// The host calls `execute` with the record as the receiver, `this` must still be undefined.
+export const topLevel = typeof this
+export const inArrow = (() => typeof this)()
+export const object = {
+    get self() {
+        return this === object
+    },
+}
+export function method() {
+    return this
+}
+export let member
+try {
+    member = this.member
+} catch (error) {
+    member = error.name
+}
+export class Base {}
+let key = 'key'
+export const computed = new (class extends (this ?? Base) {
+    [typeof this] = true
+    self = this
+})()
+export const classKeys = [computed instanceof Base, Object.keys(computed).join(), computed.self === computed]
+export const classArguments = (function () {
+    return new (class extends arguments[0] {
+        [arguments[1]] = true
+        constructor() {
+            super()
+            this.length = arguments.length
+        }
+    })(1, 2).key
+})(Base, key)
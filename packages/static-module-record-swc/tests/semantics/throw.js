@@ -0,0 +1,5 @@
+import './_counter.js'
+
+log.push('before')
+export const value = 1
+throw new TypeError('thrown from the module')
@@ -0,0 +1,5 @@
+log.push('start')
+export const value = await Promise.resolve('resolved')
+log.push(value)
+export let later
+later = await new Promise((resolve) => resolve('later'))
@@ -0,0 +1,14 @@
+export var counter = 0
+export let a = 1,
+    b = 2
+
+for (var i = 0; i < 3; i++) counter += i
+for (var key in { x: 1, y: 2 }) log.push(key)
+;[a, b] = [b, a]
+;({ a = 10 } = {})
+
+export { i, key }
+export function run() {
+    counter++
+    a = b = 'updated'
+}
@@ -3,13 +3,13 @@ export default {
         var _ = context.globalThis;
         'use strict';
         {
-            function x1() {
+            function x() {
                 arguments;
                 const x = {
                     arguments
                 };
             }
-            function x11() {
+            function x1() {
                 return ()=>arguments;
             }
             class T {
@@ -28,5 +28,13 @@ export default {
                 };
             };
         }
+        function outer() {
+            return class extends arguments[0] {
+                [arguments[1]] = 1;
+                constructor(){
+                    super(arguments);
+                }
+            };
+        }
     }
 };
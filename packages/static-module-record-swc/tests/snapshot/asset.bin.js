@@ -0,0 +1,11 @@
+export default {
+    bindings: [
+        {
+            export: "default"
+        }
+    ],
+    execute: function(__, context) {
+        var _ = context.globalThis;
+        __.default = _.Uint8Array.from((0, _.atob)("AAEC/v9iaW4="), (c)=>c.charCodeAt(0));
+    }
+};
@@ -0,0 +1,20 @@
+export default {
+    bindings: [
+        {
+            export: "default"
+        },
+        {
+            export: "stylesheet"
+        }
+    ],
+    sideEffects: false,
+    execute: function(__) {
+        __.default = {
+            "button": "button_5dc24606",
+            "icon": "icon_5dc24606",
+            "download": "download_5dc24606"
+        };
+        const stylesheet = "/* .comment is not a class */\n.button_5dc24606, .button_5dc24606:hover > .icon_5dc24606 {\n    background: url(./bg.png) no-repeat;\n    width: calc(100% - .5em);\n}\na[href$=\".pdf\"].download_5dc24606::after {\n    content: '.not-a-class';\n}\n@media (min-width: 600px) {\n    .button_5dc24606 { padding: 1.5em; }\n}\n";
+        __.stylesheet = stylesheet;
+    }
+};
@@ -0,0 +1,12 @@
+export default {
+    bindings: [
+        {
+            export: "default"
+        }
+    ],
+    needsImportMeta: true,
+    execute: function(__, context) {
+        var _ = context.globalThis;
+        __.default = new _.URL("./asset.svg", context.importMeta.url).href;
+    }
+};
@@ -0,0 +1,11 @@
+export default {
+    bindings: [
+        {
+            export: "default"
+        }
+    ],
+    sideEffects: false,
+    execute: function(__) {
+        __.default = "Hello \"assets\"\nline 2\n";
+    }
+};
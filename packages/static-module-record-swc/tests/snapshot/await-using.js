@@ -0,0 +1,100 @@
+export default {
+    bindings: [
+        {
+            import: "connect",
+            from: 'db'
+        },
+        {
+            export: "rows"
+        }
+    ],
+    isAsync: true,
+    execute: async function(__, context) {
+        var _ = context.globalThis;
+        function addDisposableResource(env, value, async) {
+            if (value !== null && value !== void 0) {
+                if (typeof value !== "object" && typeof value !== "function") throw new _.TypeError("Object expected.");
+                var dispose, inner;
+                if (async) {
+                    if (!_.Symbol.asyncDispose) throw new _.TypeError("Symbol.asyncDispose is not defined.");
+                    dispose = value[_.Symbol.asyncDispose];
+                }
+                if (dispose === void 0) {
+                    if (!_.Symbol.dispose) throw new _.TypeError("Symbol.dispose is not defined.");
+                    dispose = value[_.Symbol.dispose];
+                    if (async) inner = dispose;
+                }
+                if (typeof dispose !== "function") throw new _.TypeError("Object not disposable.");
+                if (inner) dispose = function() {
+                    try {
+                        inner.call(this);
+                    } catch (e) {
+                        return _.Promise.reject(e);
+                    }
+                };
+                env.stack.push({
+                    value: value,
+                    dispose: dispose,
+                    async: async
+                });
+            } else if (async) {
+                env.stack.push({
+                    async: true
+                });
+            }
+            return value;
+        }
+        function disposeResources(env) {
+            function fail(e) {
+                env.error = env.hasError ? suppress(e, env.error) : e;
+                env.hasError = true;
+            }
+            function suppress(error, suppressed) {
+                var message = "An error was suppressed during disposal.";
+                if (typeof _.SuppressedError === "function") return new _.SuppressedError(error, suppressed, message);
+                var e = new _.Error(message);
+                e.name = "SuppressedError";
+                e.error = error;
+                e.suppressed = suppressed;
+                return e;
+            }
+            var r, s = 0;
+            function next() {
+                while(r = env.stack.pop()){
+                    try {
+                        if (!r.async && s === 1) return s = 0, env.stack.push(r), _.Promise.resolve().then(next);
+                        if (r.dispose) {
+                            var result = r.dispose.call(r.value);
+                            if (r.async) return s |= 2, _.Promise.resolve(result).then(next, function(e) {
+                                fail(e);
+                                return next();
+                            });
+                        } else s |= 1;
+                    } catch (e) {
+                        fail(e);
+                    }
+                }
+                if (s === 1) return env.hasError ? _.Promise.reject(env.error) : _.Promise.resolve();
+                if (env.hasError) throw env.error;
+            }
+            return next();
+        }
+        var env = {
+            stack: [],
+            error: void 0,
+            hasError: false
+        };
+        try {
+            const connection = addDisposableResource(env, (0, __.connect)(), true);
+            const cursor = addDisposableResource(env, connection.cursor(), false);
+            let rows = cursor.fetch();
+            __.rows = rows;
+        } catch (e) {
+            env.error = e;
+            env.hasError = true;
+        } finally{
+            var result = disposeResources(env);
+            if (result) await result;
+        }
+    }
+};
@@ -0,0 +1,103 @@
+"use strict";
+__register("/link.js", {
+    bindings: [
+        {
+            import: "shared",
+            from: './b.js',
+            as: "local"
+        },
+        {
+            import: "onlyB",
+            from: './b.js'
+        },
+        {
+            importAllFrom: './a.js',
+            as: "ns"
+        },
+        {
+            import: "renamed",
+            from: './reexport.js'
+        },
+        {
+            import: "readFile",
+            from: 'node:fs/promises'
+        },
+        {
+            export: "onlyB",
+            from: './stars.js'
+        },
+        {
+            export: "present",
+            as: "again",
+            from: './a.js'
+        }
+    ],
+    sideEffects: false,
+    execute: function(__) {}
+});
+__register("/b.js", {
+    bindings: [
+        {
+            export: "shared"
+        },
+        {
+            export: "onlyB"
+        }
+    ],
+    sideEffects: false,
+    execute: function(__) {
+        const shared = 1;
+        __.shared = shared;
+        const onlyB = 1;
+        __.onlyB = onlyB;
+    }
+});
+__register("/a.js", {
+    bindings: [
+        {
+            export: "present"
+        }
+    ],
+    sideEffects: false,
+    execute: function(__) {
+        const present = 1;
+        __.present = present;
+    }
+});
+__register("/reexport.js", {
+    bindings: [
+        {
+            export: "present",
+            as: "renamed",
+            from: './a.js'
+        }
+    ],
+    sideEffects: false,
+    execute: function(__) {}
+});
+__register("/stars.js", {
+    bindings: [
+        {
+            export: "onlyB",
+            from: './b.js'
+        },
+        {
+            importAllFrom: './c.js',
+            as: "import_1"
+        }
+    ],
+    sideEffects: false,
+    execute: function(__) {}
+});
+__register("/c.js", {
+    bindings: [
+        {
+            export: "shared"
+        }
+    ],
+    sideEffects: false,
+    execute: function(__) {
+        const shared = 2;
+        __.shared = shared;
+    }
+});
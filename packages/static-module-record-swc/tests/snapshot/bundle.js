@@ -0,0 +1,88 @@
+"use strict";
+__register("/index.js", {
+    bindings: [
+        {
+            import: "greet",
+            from: './lib/greet.js'
+        },
+        {
+            import: "default",
+            from: './data.json',
+            as: "data"
+        },
+        {
+            import: "readFile",
+            from: 'node:fs/promises'
+        },
+        {
+            exportAllFrom: './lib/greet.js'
+        },
+        {
+            export: "message"
+        },
+        {
+            export: "lazy"
+        }
+    ],
+    needsImport: true,
+    execute: function(__, context) {
+        const message = (0, __.greet)(__.data.name);
+        __.message = message;
+        const lazy = ()=>context.import('./lib/lazy.js');
+        __.lazy = lazy;
+    }
+});
+__register("/lib/greet.js", {
+    bindings: [
+        {
+            import: "punctuation",
+            from: '../lib/../punctuation.js'
+        },
+        {
+            export: "greet"
+        }
+    ],
+    sideEffects: false,
+    execute: function(__) {
+        function greet(name) {
+            return `Hello, ${name}${__.punctuation}`;
+        }
+        __.greet = greet;
+    }
+});
+__register("/data.json", {
+    bindings: [
+        {
+            export: "default"
+        }
+    ],
+    sideEffects: false,
+    execute: function(__) {
+        __.default = {
+            "name": "bundle"
+        };
+    }
+});
+__register("/lib/lazy.js", {
+    bindings: [
+        {
+            export: "default"
+        }
+    ],
+    sideEffects: false,
+    execute: function(__) {
+        __.default = 'loaded on demand';
+    }
+});
+__register("/punctuation.js", {
+    bindings: [
+        {
+            export: "punctuation"
+        }
+    ],
+    sideEffects: false,
+    execute: function(__) {
+        const punctuation = '!';
+        __.punctuation = punctuation;
+    }
+});
@@ -0,0 +1,63 @@
+export default {
+    execute: function(__, context) {
+        var _ = context.globalThis;
+        "increment" in _.globalThis || _.Object.defineProperty(_.globalThis, "increment", {
+            value: void 0,
+            writable: true,
+            enumerable: true,
+            configurable: false
+        });
+        "counter" in _.globalThis || _.Object.defineProperty(_.globalThis, "counter", {
+            value: void 0,
+            writable: true,
+            enumerable: true,
+            configurable: false
+        });
+        "name" in _.globalThis || _.Object.defineProperty(_.globalThis, "name", {
+            value: void 0,
+            writable: true,
+            enumerable: true,
+            configurable: false
+        });
+        "unset" in _.globalThis || _.Object.defineProperty(_.globalThis, "unset", {
+            value: void 0,
+            writable: true,
+            enumerable: true,
+            configurable: false
+        });
+        "i" in _.globalThis || _.Object.defineProperty(_.globalThis, "i", {
+            value: void 0,
+            writable: true,
+            enumerable: true,
+            configurable: false
+        });
+        "key" in _.globalThis || _.Object.defineProperty(_.globalThis, "key", {
+            value: void 0,
+            writable: true,
+            enumerable: true,
+            configurable: false
+        });
+        "nested" in _.globalThis || _.Object.defineProperty(_.globalThis, "nested", {
+            value: void 0,
+            writable: true,
+            enumerable: true,
+            configurable: false
+        });
+        _.increment = function increment() {
+            return ++_.counter;
+        };
+        _.counter = 0, { name: _.name } = {
+            name: 'classic'
+        };
+        let scoped = (0, _.increment)();
+        for(_.i = 0; _.i < 2; _.i++)(0, _.increment)();
+        for(_.key in {
+            a: 1
+        })_.console.log(_.key);
+        if (_.counter) {
+            _.nested = _.globalThis;
+        }
+        _.implicit = scoped;
+        _.Reflect.deleteProperty(_.globalThis, "unset");
+    }
+};
@@ -0,0 +1,13 @@
+export default {
+    bindings: [
+        {
+            export: "a"
+        }
+    ],
+    sideEffects: false,
+    hash: "528e7ab343939bfa2ebc0efe29d95c639f3fba68f0dc5c9c98aca57e05b99828",
+    execute: function(__) {
+        const a = 1;
+        __.a = a;
+    }
+};
@@ -5,6 +5,7 @@ __register("node:fs", {
             export: "writeFileSync"
         }
     ],
+    sideEffects: false,
     execute: function(__) {
         function writeFileSync() {}
         __.writeFileSync = writeFileSync;
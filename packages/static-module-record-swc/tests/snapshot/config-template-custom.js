@@ -0,0 +1,13 @@
+"use strict";
+globalThis.register("/tests/fixture/config-template-custom.js", "2bde9680adddd79ba372dc1caf148b29cd4ba9c222097a059501806f2e5ec282", {
+    bindings: [
+        {
+            export: "a"
+        }
+    ],
+    sideEffects: false,
+    execute: function(__) {
+        const a = 1;
+        __.a = a;
+    }
+});
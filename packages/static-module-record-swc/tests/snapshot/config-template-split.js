@@ -0,0 +1,5 @@
+"use strict";
+__registerExecute("/tests/fixture/config-template-split.js", function(__) {
+    const b = __.a + 1;
+    __.b = b;
+});
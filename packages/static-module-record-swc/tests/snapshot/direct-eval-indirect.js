@@ -0,0 +1,11 @@
+export default {
+    execute: function(__, context) {
+        var _ = context.globalThis;
+        const local = 1;
+        (0, _.eval)('local');
+        {
+            const eval = (code)=>code;
+            eval('not a direct eval');
+        }
+    }
+};
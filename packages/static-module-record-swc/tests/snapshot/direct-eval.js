@@ -0,0 +1,153 @@
+export default {
+    bindings: [
+        {
+            import: "imported",
+            from: 'mod'
+        },
+        {
+            export: "counter"
+        },
+        {
+            export: "f"
+        },
+        {
+            export: "g"
+        }
+    ],
+    needsEvaluate: true,
+    execute: function(__, context) {
+        let counter = 0;
+        __.counter = counter;
+        const constant = 1;
+        function outer(param) {
+            let inner = param;
+            {
+                const shadowed = 2;
+                return context.evaluate({
+                    arguments,
+                    get shadowed () {
+                        return shadowed;
+                    },
+                    get inner () {
+                        return inner;
+                    },
+                    set inner (value){
+                        inner = value;
+                    },
+                    get param () {
+                        return param;
+                    },
+                    set param (value){
+                        param = value;
+                    },
+                    get imported () {
+                        return __.imported;
+                    },
+                    get counter () {
+                        return counter;
+                    },
+                    set counter (value){
+                        [
+                            counter = value,
+                            __.counter = counter
+                        ][0];
+                    },
+                    get constant () {
+                        return constant;
+                    },
+                    get outer () {
+                        return outer;
+                    },
+                    set outer (value){
+                        outer = value;
+                    },
+                    get f () {
+                        return f;
+                    },
+                    get g () {
+                        return g;
+                    }
+                }, 'inner + shadowed + constant + counter++ + imported');
+            }
+        }
+        const f = function named() {
+            var hoisted;
+            return context.evaluate({
+                arguments,
+                get hoisted () {
+                    return hoisted;
+                },
+                set hoisted (value){
+                    hoisted = value;
+                },
+                get named () {
+                    return named;
+                },
+                get imported () {
+                    return __.imported;
+                },
+                get counter () {
+                    return counter;
+                },
+                set counter (value){
+                    [
+                        counter = value,
+                        __.counter = counter
+                    ][0];
+                },
+                get constant () {
+                    return constant;
+                },
+                get outer () {
+                    return outer;
+                },
+                set outer (value){
+                    outer = value;
+                },
+                get f () {
+                    return f;
+                },
+                get g () {
+                    return g;
+                }
+            }, arguments[0]);
+        };
+        __.f = f;
+        outer(1);
+        const g = {
+            get value () {
+                return context.evaluate({
+                    arguments,
+                    get imported () {
+                        return __.imported;
+                    },
+                    get counter () {
+                        return counter;
+                    },
+                    set counter (value){
+                        [
+                            counter = value,
+                            __.counter = counter
+                        ][0];
+                    },
+                    get constant () {
+                        return constant;
+                    },
+                    get outer () {
+                        return outer;
+                    },
+                    set outer (value){
+                        outer = value;
+                    },
+                    get f () {
+                        return f;
+                    },
+                    get g () {
+                        return g;
+                    }
+                }, 'arguments');
+            }
+        };
+        __.g = g;
+    }
+};
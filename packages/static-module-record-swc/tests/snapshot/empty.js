@@ -1,3 +1,4 @@
 export default {
+    sideEffects: false,
     execute: function(__) {}
 };
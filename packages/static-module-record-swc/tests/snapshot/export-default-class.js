@@ -4,8 +4,11 @@ export default {
             export: "default"
         }
     ],
+    sideEffects: false,
     execute: function(__) {
-        __.default = class {
-        };
+        __.default = {
+            default: class {
+            }
+        }.default;
     }
 };
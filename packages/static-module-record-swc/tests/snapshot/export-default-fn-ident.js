@@ -4,6 +4,7 @@ export default {
             export: "default"
         }
     ],
+    sideEffects: false,
     execute: function(__) {
         function x() {
             [
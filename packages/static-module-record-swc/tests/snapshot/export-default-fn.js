@@ -4,7 +4,10 @@ export default {
             export: "default"
         }
     ],
+    sideEffects: false,
     execute: function(__) {
-        __.default = function() {};
+        __.default = {
+            default: function() {}
+        }.default;
     }
 };
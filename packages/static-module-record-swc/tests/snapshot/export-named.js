@@ -35,7 +35,7 @@ export default {
             }
         }
         {
-            for(var f = 0; f < [].length; f++){
+            for(var f = 0; f < [].length; f++, __.f = f){
                 __.f = f;
             }
             for (f of []){
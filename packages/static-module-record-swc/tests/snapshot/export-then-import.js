@@ -0,0 +1,32 @@
+export default {
+    bindings: [
+        {
+            export: "x",
+            from: 'mod'
+        },
+        {
+            export: "x",
+            as: "y",
+            from: 'mod'
+        },
+        {
+            exportAllFrom: 'mod2',
+            as: "default"
+        },
+        {
+            export: "x",
+            as: "w",
+            from: 'other'
+        },
+        {
+            import: "x",
+            from: 'mod'
+        },
+        {
+            importAllFrom: 'mod2',
+            as: "z"
+        }
+    ],
+    sideEffects: false,
+    execute: function(__) {}
+};
@@ -5,5 +5,6 @@ export default {
             as: "import_1"
         }
     ],
+    sideEffects: false,
     execute: function(__) {}
 };
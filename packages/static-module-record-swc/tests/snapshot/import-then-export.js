@@ -42,5 +42,6 @@ export default {
             as: "z"
         }
     ],
+    sideEffects: false,
     execute: function(__) {}
 };
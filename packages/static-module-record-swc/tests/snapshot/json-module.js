@@ -0,0 +1,23 @@
+export default {
+    bindings: [
+        {
+            export: "default"
+        }
+    ],
+    sideEffects: false,
+    execute: function(__) {
+        __.default = {
+            "name": "json-module",
+            "version": 1,
+            "negative": -1.5,
+            "nested": [
+                true,
+                false,
+                null,
+                {
+                    ["__proto__"]: "own property"
+                }
+            ]
+        };
+    }
+};
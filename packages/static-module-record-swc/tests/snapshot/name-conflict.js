@@ -1,11 +1,11 @@
 export default {
     needsImportMeta: true,
     needsImport: true,
-    execute: function(__, context1) {
-        var _1 = context1.globalThis;
-        var _, context;
-        _1.console.log(_, context);
-        context1.importMeta;
-        context1.import('');
+    execute: function(__, context) {
+        var _ = context.globalThis;
+        var _1, context1;
+        _.console.log(_1, context1);
+        context.importMeta;
+        context.import('');
     }
 };
@@ -0,0 +1,146 @@
+export default {
+    imports: [
+        "mod",
+        "mod2",
+        "mod3",
+        "mod4",
+        "mod5"
+    ],
+    exports: [
+        "d",
+        "e-f",
+        "all",
+        "fixed",
+        "live",
+        "update",
+        "default"
+    ],
+    reexports: [
+        "mod4"
+    ],
+    __syncModuleProgram__: "" + (({ imports: imports, liveVar: liveVar, onceVar: onceVar, importMeta: importMeta })=>{
+        let a, c, ns;
+        imports([
+            [
+                "mod",
+                [
+                    [
+                        "default",
+                        [
+                            (value1)=>a = value1
+                        ]
+                    ],
+                    [
+                        "b",
+                        [
+                            (value1)=>c = value1
+                        ]
+                    ]
+                ]
+            ],
+            [
+                "mod2",
+                [
+                    [
+                        "*",
+                        [
+                            (value1)=>ns = value1
+                        ]
+                    ]
+                ]
+            ],
+            [
+                "mod3",
+                []
+            ],
+            [
+                "mod4",
+                []
+            ],
+            [
+                "mod5",
+                []
+            ]
+        ], [
+            "mod4"
+        ]);
+        (function(__, context) {
+            var _ = context.globalThis;
+            const fixed = __.a + __.c;
+            __.fixed = fixed;
+            let live = 0;
+            __.live = live;
+            function update() {
+                live++, __.live = live;
+            }
+            __.update = update;
+            __.default = {
+                default: class {
+                }
+            }.default;
+            _.console.log(__.ns, context.importMeta.url);
+        })({
+            get a () {
+                return a;
+            },
+            get c () {
+                return c;
+            },
+            get ns () {
+                return ns;
+            },
+            set fixed (value){
+                onceVar.fixed(value);
+            },
+            set live (value){
+                liveVar.live(value);
+            },
+            set update (value){
+                liveVar.update(value);
+            },
+            set "default" (value){
+                onceVar["default"](value);
+            }
+        }, {
+            globalThis: globalThis,
+            importMeta: importMeta
+        });
+    }),
+    __liveExportMap__: {
+        live: [
+            "live",
+            false
+        ],
+        update: [
+            "update",
+            false
+        ]
+    },
+    __fixedExportMap__: {
+        fixed: [
+            "fixed"
+        ],
+        "default": [
+            "default"
+        ]
+    },
+    __reexportMap__: {
+        mod3: [
+            [
+                "d",
+                "d"
+            ],
+            [
+                "e",
+                "e-f"
+            ]
+        ],
+        mod5: [
+            [
+                "*",
+                "all"
+            ]
+        ]
+    },
+    __needsImportMeta__: true
+};
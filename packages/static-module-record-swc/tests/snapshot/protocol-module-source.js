@@ -0,0 +1,19 @@
+export default {
+    bindings: [
+        {
+            import: "a",
+            from: 'mod'
+        },
+        {
+            export: "b"
+        }
+    ],
+    isAsync: true,
+    needsImportMeta: true,
+    needsImport: true,
+    initialize: async function(__, context) {
+        const b = __.a;
+        __.b = b;
+        await context.import(context.importMeta.url);
+    }
+};
@@ -26,5 +26,6 @@ export default {
             from: 'mod3'
         }
     ],
+    sideEffects: false,
     execute: function(__) {}
 };
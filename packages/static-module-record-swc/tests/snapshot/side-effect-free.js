@@ -0,0 +1,64 @@
+export default {
+    bindings: [
+        {
+            import: "createContext",
+            from: 'lib'
+        },
+        {
+            import: "Base",
+            from: 'lib'
+        },
+        {
+            export: "Context"
+        },
+        {
+            export: "config"
+        },
+        {
+            export: "enabled"
+        },
+        {
+            export: "helper"
+        },
+        {
+            export: "Component"
+        },
+        {
+            export: "default"
+        }
+    ],
+    sideEffects: false,
+    execute: function(__, context) {
+        var _ = context.globalThis;
+        const Context = (0, __.createContext)({
+            value: null
+        });
+        __.Context = Context;
+        const config = {
+            name: 'config',
+            items: [
+                1,
+                2,
+                3
+            ],
+            get size () {
+                return 3;
+            }
+        };
+        __.config = config;
+        const enabled = typeof config === 'object' && !Context;
+        __.enabled = enabled;
+        function helper() {
+            return (0, _.sideEffect)();
+        }
+        __.helper = helper;
+        class Component extends __.Base {
+            static displayName = 'Component';
+            render() {}
+        }
+        __.Component = Component;
+        __.default = {
+            default: ()=>helper()
+        }.default;
+    }
+};
@@ -0,0 +1,96 @@
+export default {
+    execute: function(__, context) {
+        var _ = context.globalThis;
+        "reserved" in _.globalThis || _.Object.defineProperty(_.globalThis, "reserved", {
+            value: void 0,
+            writable: true,
+            enumerable: true,
+            configurable: false
+        });
+        "linked" in _.globalThis || _.Object.defineProperty(_.globalThis, "linked", {
+            value: void 0,
+            writable: true,
+            enumerable: true,
+            configurable: false
+        });
+        "blocks" in _.globalThis || _.Object.defineProperty(_.globalThis, "blocks", {
+            value: void 0,
+            writable: true,
+            enumerable: true,
+            configurable: false
+        });
+        "callee" in _.globalThis || _.Object.defineProperty(_.globalThis, "callee", {
+            value: void 0,
+            writable: true,
+            enumerable: true,
+            configurable: false
+        });
+        "global" in _.globalThis || _.Object.defineProperty(_.globalThis, "global", {
+            value: void 0,
+            writable: true,
+            enumerable: true,
+            configurable: false
+        });
+        "x" in _.globalThis || _.Object.defineProperty(_.globalThis, "x", {
+            value: void 0,
+            writable: true,
+            enumerable: true,
+            configurable: false
+        });
+        _.reserved = function reserved(_static, _a, a) {
+            var _yield = {
+                yield: function() {}
+            }.yield;
+            var _arguments = arguments;
+            var _arguments = [
+                _static
+            ];
+            return [
+                _arguments,
+                a,
+                _yield
+            ];
+        };
+        _.linked = function linked(a) {
+            0 < arguments.length && _.Object.defineProperty(arguments, 0, {
+                get: ()=>a,
+                set: (value)=>a = value,
+                enumerable: true,
+                configurable: true
+            });
+            a = 1;
+            return arguments[0];
+        };
+        _.blocks = function blocks() {
+            var inner, set_inner = (value)=>inner = value;
+            {
+                function inner() {}
+                set_inner(inner);
+            }
+            return inner;
+        };
+        _.callee = function callee() {
+            return callee;
+        };
+        const hasBinding = (object, name)=>{
+            if (!(name in object)) return false;
+            var unscopables = object[_.Symbol.unscopables];
+            return !(unscopables !== null && (typeof unscopables === "object" || typeof unscopables === "function") && unscopables[name]);
+        };
+        {
+            function global() {}
+            _.global = global;
+        }
+        1;
+        {
+            ;
+            const value = _.object;
+            if (value == null) throw new _.TypeError("Cannot convert undefined or null to object");
+            const object = (0, _.Object)(value);
+            {
+                hasBinding(object, "x") ? object.x = hasBinding(object, "y") ? object.y : _.y : _.x = hasBinding(object, "y") ? object.y : _.y;
+                hasBinding(object, "f") ? object.f(hasBinding(object, "x") ? object.x : _.x) : (0, _.f)(hasBinding(object, "x") ? object.x : _.x);
+            }
+        }
+    }
+};
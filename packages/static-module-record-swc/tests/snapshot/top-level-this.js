@@ -0,0 +1,57 @@
+export default {
+    bindings: [
+        {
+            export: "self"
+        },
+        {
+            export: "method"
+        },
+        {
+            export: "Class"
+        },
+        {
+            export: "object"
+        },
+        {
+            export: "Derived"
+        }
+    ],
+    execute: function(__, context) {
+        var _ = context.globalThis;
+        const self = (void 0);
+        __.self = self;
+        const arrow = ()=>(void 0)?.value;
+        if (typeof (void 0) === 'undefined') arrow();
+        function method() {
+            return this;
+        }
+        __.method = method;
+        class Class {
+            field = this;
+            static{
+                this.static = true;
+            }
+        }
+        __.Class = Class;
+        const object = {
+            get getter () {
+                return this;
+            },
+            set setter (value){
+                this.value = value;
+            },
+            method () {
+                return this;
+            }
+        };
+        __.object = object;
+        (void 0).member;
+        class Derived extends ((void 0) ?? _.Object) {
+            [(void 0)?.key] = this;
+            static [(void 0)?.key]() {
+                return this;
+            }
+        }
+        __.Derived = Derived;
+    }
+};
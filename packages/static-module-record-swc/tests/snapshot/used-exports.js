@@ -0,0 +1,26 @@
+export default {
+    bindings: [
+        {
+            import: "helper",
+            from: './helper.js'
+        },
+        {
+            importAllFrom: './other.js',
+            as: "reexport_1"
+        },
+        {
+            export: "used"
+        },
+        {
+            export: "usedAlias"
+        }
+    ],
+    execute: function(__, context) {
+        var _ = context.globalThis;
+        const used = (0, __.helper)();
+        __.used = used;
+        const local = 2;
+        __.usedAlias = local;
+        let impure = (0, _.sideEffect)();
+    }
+};
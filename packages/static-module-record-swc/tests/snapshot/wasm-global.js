@@ -0,0 +1,41 @@
+export default {
+    bindings: [
+        {
+            export: "increment"
+        },
+        {
+            export: "counter"
+        },
+        {
+            export: "answer"
+        }
+    ],
+    isAsync: true,
+    execute: async function(__, context) {
+        var _ = context.globalThis;
+        const { instance } = await _.WebAssembly.instantiate(_.Uint8Array.from("\x00asm\x01\x00\x00\x00\x01\x04\x01`\x00\x00\x03\x02\x01\x00\x06\x0b\x02\x7f\x01A\x00\x0b\x7f\x00A*\x0b\x07 \x03\x09increment\x00\x00\x07counter\x03\x00\x06answer\x03\x01\x0a\x0b\x01\x09\x00#\x00A\x01j$\x00\x0b", (c)=>c.charCodeAt(0)), {});
+        const update = ()=>{
+            [
+                export1 = instance.exports["counter"].value,
+                __["counter"] = export1
+            ][0];
+        };
+        const wrap = (f)=>_.Object.defineProperty({
+                [f.name] (...args) {
+                    try {
+                        return f(...args);
+                    } finally{
+                        update();
+                    }
+                }
+            }[f.name], "length", {
+                value: f.length
+            });
+        const export0 = wrap(instance.exports["increment"]);
+        __["increment"] = export0;
+        let export1 = instance.exports["counter"].value;
+        __["counter"] = export1;
+        const export2 = instance.exports["answer"].value;
+        __["answer"] = export2;
+    }
+};
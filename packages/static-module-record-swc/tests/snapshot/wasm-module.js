@@ -0,0 +1,36 @@
+export default {
+    bindings: [
+        {
+            import: "log",
+            from: "env",
+            as: "import0"
+        },
+        {
+            import: "mem",
+            from: "js",
+            as: "import1"
+        },
+        {
+            export: "add"
+        },
+        {
+            export: "re-exported log"
+        }
+    ],
+    isAsync: true,
+    execute: async function(__, context) {
+        var _ = context.globalThis;
+        const { instance } = await _.WebAssembly.instantiate(_.Uint8Array.from("\x00asm\x01\x00\x00\x00\x01\x0b\x02`\x01\x7f\x00`\x02\x7f\x7f\x01\x7f\x02\x15\x02\x03env\x03log\x00\x00\x02js\x03mem\x02\x00\x01\x03\x02\x01\x01\x07\x19\x02\x03add\x00\x01\x0fre-exported log\x00\x00\x0a\x09\x01\x07\x00 \x00 \x01j\x0b", (c)=>c.charCodeAt(0)), {
+            "env": {
+                "log": __.import0
+            },
+            "js": {
+                "mem": __.import1
+            }
+        });
+        const export0 = instance.exports["add"];
+        __["add"] = export0;
+        const export1 = instance.exports["re-exported log"];
+        __["re-exported log"] = export1;
+    }
+};
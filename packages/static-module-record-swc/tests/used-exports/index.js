@@ -0,0 +1,11 @@
+import { helper } from './helper.js'
+export { unusedReexport } from './other.js'
+export const used = helper()
+export const unused = /*#__PURE__*/ make()
+export function unusedFn() {
+    return unused
+}
+const local = 2
+export { local as unusedAlias, local as usedAlias }
+export let impure = sideEffect()
+export default function () {}